@@ -1,86 +1,407 @@
-use crate::fns::{get_os_release_pretty_name, uname_s};
-
-macro_rules! ascii {
-    ($overriden_ascii:expr, $search_string:expr, $return_string:expr) => {{
-        let os_name = $overriden_ascii
-            .clone()
-            .map(|o| o.to_ascii_lowercase())
-            .unwrap_or_else(|| {
-                get_os_release_pretty_name(None, "ID")
-                    .unwrap_or_default()
-                    .to_ascii_lowercase()
-            });
-        let uname = uname_s($overriden_ascii.clone()).to_ascii_lowercase();
-
-        if os_name.contains(&$search_string.to_ascii_lowercase())
-            || uname.contains(&$search_string.to_ascii_lowercase())
-        {
-            $return_string.to_string()
-        } else {
-            "".to_string()
+use crate::color_config::parse_color;
+use crate::fns::{get_os_release, home_dir, terminal_size, uname_s};
+use colored::Color;
+use std::{collections::HashMap, fs};
+
+// Built-in logos, in matching order. User logos from `~/.config/rsftch/logos/` are
+// checked before these, and replace a built-in when they share its id.
+const BUILTIN_LOGOS: [(&str, &str); 22] = [
+    ("arch", include_str!("logos/arch.txt")),
+    ("debian", include_str!("logos/debian.txt")),
+    ("fedora", include_str!("logos/fedora.txt")),
+    ("endeavouros", include_str!("logos/endeavouros.txt")),
+    ("manjaro", include_str!("logos/manjaro.txt")),
+    ("void", include_str!("logos/void.txt")),
+    ("ubuntu", include_str!("logos/ubuntu.txt")),
+    ("opensuse", include_str!("logos/opensuse.txt")),
+    ("raspbian", include_str!("logos/raspbian.txt")),
+    ("linuxmint", include_str!("logos/linuxmint.txt")),
+    ("mx", include_str!("logos/mx.txt")),
+    ("gentoo", include_str!("logos/gentoo.txt")),
+    ("funtoo", include_str!("logos/funtoo.txt")),
+    ("slackware", include_str!("logos/slackware.txt")),
+    ("uwuntu", include_str!("logos/uwuntu.txt")),
+    ("nixos", include_str!("logos/nixos.txt")),
+    ("vanilla", include_str!("logos/vanilla.txt")),
+    ("kali", include_str!("logos/kali.txt")),
+    ("cachyos", include_str!("logos/cachyos.txt")),
+    ("netbsd", include_str!("logos/netbsd.txt")),
+    ("freebsd", include_str!("logos/freebsd.txt")),
+    ("rsftch", include_str!("logos/rsftch.txt")),
+];
+
+const FALLBACK_LOGO: &str = "rsftch";
+pub const MAX_COLOR_SLOTS: usize = 6;
+
+#[derive(Clone, Debug)]
+pub struct Logo {
+    pub id: String,
+    pub name: String,
+    pub matches: Vec<String>,
+    pub colors: Vec<Color>,
+    pub art: String,
+    pub small_art: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogoSize {
+    Auto,
+    Small,
+    Large,
+    None,
+}
+
+impl LogoSize {
+    pub fn parse(size: &str) -> Option<LogoSize> {
+        match size.to_ascii_lowercase().as_str() {
+            "auto" => Some(LogoSize::Auto),
+            "small" => Some(LogoSize::Small),
+            "large" => Some(LogoSize::Large),
+            "none" => Some(LogoSize::None),
+            _ => None,
+        }
+    }
+}
+
+impl Logo {
+    /// Parses a logo file: an optional `key: value` header (`name`, `match`, `colors`)
+    /// terminated by a `---` line, followed by the art itself. A `--- small` line starts
+    /// the small variant of the art, and `--- large` switches back to the large one.
+    pub fn parse(id: &str, source: &str) -> Logo {
+        let mut logo = Logo {
+            id: id.to_string(),
+            name: id.to_string(),
+            matches: vec![],
+            colors: vec![],
+            art: String::new(),
+            small_art: None,
+        };
+
+        let (header, art) = match source.split_once("\n---\n") {
+            Some((header, art)) => (header, art),
+            None => match source.strip_prefix("---\n") {
+                Some(art) => ("", art),
+                None => ("", source),
+            },
+        };
+
+        for line in header.lines() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+                match key.trim().to_ascii_lowercase().as_str() {
+                    "name" => logo.name = value.to_string(),
+                    "match" => {
+                        logo.matches = value
+                            .split(',')
+                            .map(|s| s.trim().to_ascii_lowercase())
+                            .filter(|s| !s.is_empty())
+                            .collect()
+                    }
+                    "color" | "colors" => {
+                        logo.colors = value
+                            .split(',')
+                            .map(|s| s.trim())
+                            .filter(|s| !s.is_empty())
+                            .map(parse_color)
+                            .collect()
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut large = String::new();
+        let mut small: Option<String> = None;
+        let mut in_small = false;
+        for line in art.lines() {
+            match line.trim_end() {
+                "--- small" => {
+                    in_small = true;
+                    small.get_or_insert_with(String::new);
+                }
+                "--- large" => in_small = false,
+                _ => {
+                    let variant = match (in_small, &mut small) {
+                        (true, Some(small)) => small,
+                        _ => &mut large,
+                    };
+                    variant.push_str(line);
+                    variant.push('\n');
+                }
+            }
+        }
+
+        logo.art = large.trim_end_matches('\n').to_string();
+        logo.small_art = small.map(|small| small.trim_end_matches('\n').to_string());
+        logo
+    }
+
+    /// Returns this logo with its art swapped for the requested variant, or `None` when no
+    /// logo should be shown. User logos without small art get a box with their name instead.
+    pub fn sized(&self, size: LogoSize) -> Option<Logo> {
+        let art = match size {
+            LogoSize::None => return None,
+            LogoSize::Auto | LogoSize::Large => self.art.clone(),
+            LogoSize::Small => self.small_art.clone().unwrap_or_else(|| {
+                let border = "─".repeat(self.name.chars().count() + 2);
+                format!("╭{}╮\n│ {} │\n╰{}╯", border, self.name, border)
+            }),
+        };
+
+        Some(Logo {
+            art,
+            ..self.clone()
+        })
+    }
+
+    /// Width and height of the art in terminal cells.
+    pub fn dimensions(&self) -> (usize, usize) {
+        let plain_art = self.plain_art();
+        let width = plain_art
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        (width, plain_art.lines().count())
+    }
+
+    /// Splits the art on its `${cN}` placeholders into (color slot, text) pairs.
+    /// Text before the first placeholder uses slot 1.
+    pub fn segments(&self) -> Vec<(usize, &str)> {
+        let mut segments = vec![];
+        let mut slot = 1;
+        let mut rest = self.art.as_str();
+
+        while let Some(start) = rest.find("${c") {
+            let placeholder = &rest[start + 3..];
+            let Some(end) = placeholder.find('}') else {
+                break;
+            };
+            let Ok(next_slot) = placeholder[..end].parse::<usize>() else {
+                segments.push((slot, &rest[..start + 3]));
+                rest = placeholder;
+                continue;
+            };
+
+            segments.push((slot, &rest[..start]));
+            slot = next_slot.clamp(1, MAX_COLOR_SLOTS);
+            rest = &placeholder[end + 1..];
+        }
+        segments.push((slot, rest));
+
+        segments.retain(|(_, text)| !text.is_empty());
+        segments
+    }
+
+    /// The art with every color placeholder removed.
+    pub fn plain_art(&self) -> String {
+        self.segments().iter().map(|(_, text)| *text).collect()
+    }
+}
+
+/// A logo id for `name`, in the form of an os-release `ID`: "Arch Linux" becomes "arch-linux".
+pub fn logo_id(name: &str) -> String {
+    name.to_ascii_lowercase()
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+fn load_user_logos() -> Vec<Logo> {
+    let dir = format!("{}/.config/rsftch/logos", home_dir());
+    let mut paths: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect(),
+        Err(_) => return vec![],
+    };
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_ascii_lowercase();
+            let source = fs::read_to_string(path).ok()?;
+            Some(Logo::parse(&id, &source))
+        })
+        .collect()
+}
+
+fn builtin_logos() -> impl Iterator<Item = Logo> {
+    BUILTIN_LOGOS
+        .iter()
+        .map(|(id, source)| Logo::parse(id, source))
+}
+
+pub fn get_logos() -> Vec<Logo> {
+    let mut logos = load_user_logos();
+
+    for builtin in builtin_logos() {
+        if !logos.iter().any(|logo| logo.id == builtin.id) {
+            logos.push(builtin);
         }
-    }};
+    }
+
+    logos
 }
 
 pub fn ascii_test() {
-    let distros = [
-        "Arch Linux",
-        "Debian",
-        "Fedora",
-        "EndeavourOS",
-        "Void",
-        "Ubuntu",
-        "Suse",
-        "Raspbian",
-        "Linux Mint",
-        "MX Linux",
-        "Gentoo",
-        "Funtoo",
-        "Slackware",
-        "UwUntu",
-        "NixOS",
-        "VanillaOS",
-        "Kali Linux",
-        "CachyOS",
-        "NetBSD",
-        "FreeBSD",
-        "Unknown distro",
-    ];
-
-    for i in distros {
-        println!("\n{i}: \n{}", get_distro_ascii(Some(i.to_string())));
+    for logo in get_logos() {
+        println!("\n{} ({}): \n{}", logo.name, logo.id, logo.plain_art());
+    }
+}
+
+// Ids to look a logo up by, most specific first: the override (as typed and without
+// spaces), or the os-release `ID`, then each `ID_LIKE` parent, then `uname -s`.
+fn get_distro_ids(overriden_ascii: Option<String>) -> Vec<String> {
+    if let Some(overriden) = overriden_ascii {
+        let overriden = overriden.to_ascii_lowercase();
+        return vec![overriden.replace(' ', ""), overriden];
     }
+
+    get_distro_ids_from(&get_os_release(), &uname_s(None))
+}
+
+fn get_distro_ids_from(os_release: &HashMap<String, String>, uname: &str) -> Vec<String> {
+    let mut ids: Vec<String> = os_release.get("ID").into_iter().cloned().collect();
+    if let Some(id_like) = os_release.get("ID_LIKE") {
+        ids.extend(id_like.split_whitespace().map(|id| id.to_string()));
+    }
+    ids.push(uname.to_string());
+
+    ids.iter()
+        .map(|id| id.trim().to_ascii_lowercase())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+fn find_logo(logos: &[Logo], ids: &[String]) -> Option<Logo> {
+    ids.iter()
+        .find_map(|id| {
+            logos.iter().find(|logo| {
+                logo.id == *id || logo.matches.contains(id) || logo.name.to_ascii_lowercase() == *id
+            })
+        })
+        .cloned()
 }
 
-pub fn get_distro_ascii(overriden_ascii: Option<String>) -> String {
-    let ascii_macros = vec![
-        ascii!(overriden_ascii, "arch", "   ___               __ \n  / _ |  ____ ____  / / \n / __ | / __// __/ / _ \\\n/_/ |_|/_/   \\__/ /_//_/"),
-        ascii!(overriden_ascii, "debian", "   ___      __   _         \n  / _ \\___ / /  (_)__ ____ \n / // / -_) _ \\/ / _ `/ _ \\\n/____/\\__/_.__/_/\\_,_/_//_/"),
-        ascii!(overriden_ascii, "fedora", "   ____       __             \n  / __/__ ___/ /__  _______ _\n / _// -_) _  / _ \\/ __/ _ `/\n/_/  \\__/\\_,_/\\___/_/  \\_,_/"),
-        ascii!(overriden_ascii, "endeavour", "   ____        __                           \n  / __/__  ___/ /__ ___ __  _____  __ ______\n / _// _ \\/ _  / -_) _ `/ |/ / _ \\/ // / __/\n/___/_//_/\\_,_/\\__/\\_,_/|___/\\___/\\_,_/_/"),
-        ascii!(overriden_ascii, "void", "  _   __     _    __\n | | / /__  (_)__/ /\n | |/ / _ \\/ / _  / \n |___/\\___/_/\\_,_/"),
-        ascii!(overriden_ascii, "ubuntu", "  __  ____             __      \n / / / / /  __ _____  / /___ __\n/ /_/ / _ \\/ // / _ \\/ __/ // /\n\\____/_.__/\\_,_/_//_/\\__/\\_,_/"),
-        ascii!(overriden_ascii, "suse", "  ____                ____            \n / __ \\___  ___ ___  / __/_ _____ ___ \n/ /_/ / _ \\/ -_) _ \\_\\ \\/ // (_-</ -_)\n\\____/ .__/\\__/_//_/___/\\_,_/___/\\__/ \n    /_/"),
-        ascii!(overriden_ascii, "rasp", "   ___                __   _         \n  / _ \\___ ____ ___  / /  (_)__ ____ \n / , _/ _ `(_-</ _ \\/ _ \\/ / _ `/ _ \\\n/_/|_|\\_,_/___/ .__/_.__/_/\\_,_/_//_/\n             /_/"),
-        ascii!(overriden_ascii, "mint", "   __  ____      __ \n  /  |/  (_)__  / /_\n / /|_/ / / _ \\/ __/\n/_/  /_/_/_//_/\\__/"),
-        ascii!(overriden_ascii, "mx", "   __  ____  __  __   _               \n  /  |/  / |/_/ / /  (_)__  __ ____ __\n / /|_/ />  <  / /__/ / _ \\/ // /\\ \\ /\n/_/  /_/_/|_| /____/_/_//_/\\_,_//_\\_\\"),
-        ascii!(overriden_ascii, "gentoo", "  _____         __          \n / ___/__ ___  / /____  ___ \n/ (_ / -_) _ \\/ __/ _ \\/ _ \\\n\\___/\\__/_//_/\\__/\\___/\\___/"),
-        ascii!(overriden_ascii, "funtoo", "   ____          __          \n  / __/_ _____  / /____  ___ \n / _// // / _ \\/ __/ _ \\/ _ \n/_/  \\_,_/_//_/\\__/\\___/\\___/"),
-        ascii!(overriden_ascii, "slack", "   ______         __                      \n  / __/ /__ _____/ /___    _____ ________ \n _\\ \\/ / _ `/ __/  '_/ |/|/ / _ `/ __/ -_)\n/___/_/\\_,_/\\__/_/\\_\\|__,__/\\_,_/_/  \\__/ "),
-        ascii!(overriden_ascii, "uwuntu", "  __  __       __  __     __      \n / / / /    __/ / / /__  / /___ __\n/ /_/ / |/|/ / /_/ / _ \\/ __/ // /\n\\____/|__,__/\\____/_//_/\\__/\\_,_/"),
-        ascii!(overriden_ascii, "nix", "   _  ___      ____  ____\n  / |/ (_)_ __/ __ \\/ __/\n /    / /\\ \\ / /_/ /\\ \\  \n/_/|_/_//_\\_\\\\____/___/"),
-        ascii!(overriden_ascii, "vanilla", "  _   __          _ ____    \n | | / /__ ____  (_) / /__ _\n | |/ / _ `/ _ \\/ / / / _ `/\n |___/\\_,_/_//_/_/_/_/\\_,_/"),
-        ascii!(overriden_ascii, "kali", "   __ __     ___   \n  / //_/__ _/ (_)  \n / ,< / _ `/ / /   \n/_/|_|\\_,_/_/_/"),
-        ascii!(overriden_ascii, "cachy", "  _____         __       \n / ___/__ _____/ /  __ __\n/ /__/ _ `/ __/ _ \\/ // /\n\\___/\\_,_/\\__/_//_/\\_, / \n                  /___/"),
-        ascii!(overriden_ascii, "netbsd", "   _  __    __  ___  _______ \n  / |/ /__ / /_/ _ )/ __/ _ \\\n /    / -_) __/ _  |\\ \\/ // /\n/_/|_/\\__/\\__/____/___/____/ "),
-        ascii!(overriden_ascii, "freebsd", "   ___            ___  _______ \n  / _/______ ___ / _ )/ __/ _ \\\n / _/ __/ -_) -_) _  |\\ \\/ // /\n/_//_/  \\__/\\__/____/___/____/")
-    ];
-
-    for i in ascii_macros {
-        if !i.is_empty() {
-            return i;
+/// Picks the largest logo variant that fits in the terminal next to `info_height` lines of
+/// info, or no logo at all when even the small one doesn't fit.
+pub fn get_logo_size(logo: &Logo, info_height: usize) -> LogoSize {
+    let Some((columns, lines)) = terminal_size() else {
+        return LogoSize::Large;
+    };
+
+    [LogoSize::Large, LogoSize::Small]
+        .into_iter()
+        .find(|size| {
+            logo.sized(*size).is_some_and(|sized| {
+                let (width, height) = sized.dimensions();
+                // The logo is followed by an empty line before the info.
+                width <= columns && height + 1 + info_height <= lines
+            })
+        })
+        .unwrap_or(LogoSize::None)
+}
+
+pub fn get_distro_ascii(overriden_ascii: Option<String>) -> Logo {
+    let logos = get_logos();
+
+    find_logo(&logos, &get_distro_ids(overriden_ascii))
+        .or_else(|| find_logo(&logos, &[FALLBACK_LOGO.to_string()]))
+        .unwrap_or_else(|| Logo::parse(FALLBACK_LOGO, ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fns::parse_os_release;
+
+    fn logo_for(os_release: &str) -> Option<String> {
+        let logos: Vec<Logo> = builtin_logos().collect();
+        let ids = get_distro_ids_from(&parse_os_release(os_release), "Linux");
+        find_logo(&logos, &ids).map(|logo| logo.id)
+    }
+
+    fn logo_for_id(id: &str) -> Option<String> {
+        let logos: Vec<Logo> = builtin_logos().collect();
+        find_logo(&logos, &[id.to_string()]).map(|logo| logo.id)
+    }
+
+    #[test]
+    fn mx_and_linux_mint_dont_collide() {
+        assert_eq!(
+            logo_for("NAME=\"MX\"\nID=\"mx\"\nID_LIKE=\"debian\"\n").as_deref(),
+            Some("mx")
+        );
+        assert_eq!(
+            logo_for("NAME=\"Linux Mint\"\nID=linuxmint\nID_LIKE=\"ubuntu debian\"\n").as_deref(),
+            Some("linuxmint")
+        );
+        assert_eq!(logo_for_id("mint").as_deref(), Some("linuxmint"));
+    }
+
+    #[test]
+    fn arch_derivatives_dont_match_by_substring() {
+        assert_eq!(logo_for_id("archcraft"), None);
+        assert_eq!(logo_for_id("arcolinux"), None);
+        assert_eq!(
+            logo_for("NAME=\"Archcraft\"\nID=archcraft\n").as_deref(),
+            None
+        );
+        assert_eq!(
+            logo_for("NAME=\"Archcraft\"\nID=archcraft\nID_LIKE=arch\n").as_deref(),
+            Some("arch")
+        );
+    }
+
+    #[test]
+    fn id_like_parents_are_tried_in_order() {
+        assert_eq!(
+            logo_for("ID=pop\nID_LIKE=\"ubuntu debian\"\n").as_deref(),
+            Some("ubuntu")
+        );
+        assert_eq!(
+            logo_for("ID=custom\nID_LIKE=\"debian ubuntu\"\n").as_deref(),
+            Some("debian")
+        );
+        // The exact ID wins over its parents.
+        assert_eq!(
+            logo_for("ID=endeavouros\nID_LIKE=arch\n").as_deref(),
+            Some("endeavouros")
+        );
+    }
+
+    #[test]
+    fn builtin_logos_have_small_variants() {
+        for logo in builtin_logos() {
+            let small = logo
+                .small_art
+                .as_ref()
+                .map(|_| logo.sized(LogoSize::Small).unwrap());
+            let (width, height) = small.expect(&logo.id).dimensions();
+            assert!((2..=4).contains(&height), "{} is {} lines", logo.id, height);
+            assert!(
+                width <= logo.dimensions().0,
+                "{} is wider when small",
+                logo.id
+            );
         }
     }
 
-    "   ___      _____      __ \n  / _ \\___ / _/ /_____/ / \n / , _(_-</ _/ __/ __/ _ \\\n/_/|_/___/_/ \\__/\\__/_//_/".to_string()
+    #[test]
+    fn uname_is_the_last_resort() {
+        let logos: Vec<Logo> = builtin_logos().collect();
+        let ids = get_distro_ids_from(&HashMap::new(), "NetBSD");
+        assert_eq!(
+            find_logo(&logos, &ids).map(|logo| logo.id).as_deref(),
+            Some("netbsd")
+        );
+    }
 }
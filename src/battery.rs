@@ -0,0 +1,284 @@
+use crate::fns::read_trimmed;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug)]
+pub struct Battery {
+    pub name: String,
+    pub capacity: Option<u32>,
+    /// "full", "high", "normal", "low" or "critical", for peripherals that don't report a
+    /// percentage.
+    pub level: Option<String>,
+    pub status: Option<String>,
+    /// Full capacity as a percentage of the design capacity.
+    pub health: Option<u32>,
+    /// Minutes until empty when discharging, or until full when charging.
+    pub time_left: Option<u64>,
+    /// Batteries of peripherals like mice and controllers, rather than the system.
+    pub peripheral: bool,
+}
+
+impl Battery {
+    /// "85% [discharging, 2h 15m left, 92% health]"
+    pub fn format(&self, ac_online: bool) -> String {
+        let charge = match (self.capacity, &self.level) {
+            (Some(capacity), _) => format!("{}%", capacity),
+            (None, level) => level.clone().unwrap_or_default(),
+        };
+        let time_left = self.time_left.map(|minutes| {
+            let direction = match self.status.as_deref() {
+                Some("charging") => "until full",
+                _ => "left",
+            };
+            match minutes / 60 {
+                0 => format!("{}m {}", minutes, direction),
+                hours => format!("{}h {}m {}", hours, minutes % 60, direction),
+            }
+        });
+        // Only worth mentioning when the status doesn't already make it clear.
+        let ac = (ac_online && !self.peripheral && self.status.as_deref() != Some("charging"))
+            .then(|| "AC".to_string());
+
+        let details: Vec<String> = self
+            .status
+            .clone()
+            .into_iter()
+            .chain(ac)
+            .chain(time_left)
+            .chain(self.health.map(|health| format!("{}% health", health)))
+            .collect();
+
+        match details.is_empty() {
+            true => charge,
+            false => format!("{} [{}]", charge, details.join(", ")),
+        }
+    }
+}
+
+fn read_number(path: &Path) -> Option<u64> {
+    read_trimmed(path)?.parse().ok()
+}
+
+fn read_battery(supply_path: &Path) -> Option<Battery> {
+    let value = |file: &str| read_number(&supply_path.join(file));
+    // Batteries report either energy (µWh) and power (µW), or charge (µAh) and current
+    // (µA), depending on the firmware.
+    let (now, full, design, rate) = match value("energy_full") {
+        Some(full) => (
+            value("energy_now"),
+            Some(full),
+            value("energy_full_design"),
+            value("power_now"),
+        ),
+        None => (
+            value("charge_now"),
+            value("charge_full"),
+            value("charge_full_design"),
+            value("current_now"),
+        ),
+    };
+
+    let capacity = value("capacity")
+        .or_else(|| Some(now? * 100 / full.filter(|full| *full > 0)?))
+        .map(|capacity| capacity.min(100) as u32);
+    // Some mice and keyboards only tell roughly how full they are.
+    let level = read_trimmed(&supply_path.join("capacity_level"))
+        .filter(|level| level != "Unknown")
+        .map(|level| level.to_lowercase());
+    if capacity.is_none() && level.is_none() {
+        return None;
+    }
+    let status = read_trimmed(&supply_path.join("status"))
+        .filter(|status| status != "Unknown")
+        .map(|status| status.to_lowercase());
+
+    let time_left = match (status.as_deref(), now, full, rate.filter(|rate| *rate > 0)) {
+        (Some("discharging"), Some(now), _, Some(rate)) => Some(now * 60 / rate),
+        (Some("charging"), Some(now), Some(full), Some(rate)) => {
+            Some(full.saturating_sub(now) * 60 / rate)
+        }
+        _ => None,
+    };
+    let health = match (full, design) {
+        (Some(full), Some(design)) if design > 0 => Some((full * 100 / design) as u32),
+        _ => None,
+    };
+
+    let peripheral = read_trimmed(&supply_path.join("scope")).as_deref() == Some("Device");
+    let name = match peripheral {
+        // Peripherals are named after their driver and address, like
+        // "hidpp_battery_0", the model name tells which device it is.
+        true => read_trimmed(&supply_path.join("model_name")),
+        false => None,
+    }
+    .or_else(|| Some(supply_path.file_name()?.to_string_lossy().to_string()))?;
+
+    Some(Battery {
+        name,
+        capacity,
+        level,
+        status,
+        health,
+        time_left,
+        peripheral,
+    })
+}
+
+/// The batteries among the power supplies in `power_supply_path`, and whether an AC
+/// adapter is plugged in.
+fn get_batteries_from(power_supply_path: &Path) -> (Vec<Battery>, bool) {
+    let Ok(entries) = fs::read_dir(power_supply_path) else {
+        return (vec![], false);
+    };
+
+    let mut supplies: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    supplies.sort();
+
+    let mut batteries = vec![];
+    let mut ac_online = false;
+    for supply in supplies {
+        let online = || read_trimmed(&supply.join("online")).as_deref() == Some("1");
+        let peripheral = || read_trimmed(&supply.join("scope")).as_deref() == Some("Device");
+        match read_trimmed(&supply.join("type")).as_deref() {
+            Some("Battery") => batteries.extend(read_battery(&supply)),
+            // USB-C chargers show up as "USB", older kernels had their own types for them.
+            Some("Mains" | "USB" | "USB_C" | "USB_PD" | "USB_PD_DRP") if !peripheral() => {
+                ac_online |= online()
+            }
+            _ => {}
+        }
+    }
+
+    // The system's own batteries first.
+    batteries.sort_by_key(|battery| battery.peripheral);
+    (batteries, ac_online)
+}
+
+/// The `battery` module, one row per battery, empty on machines without any.
+pub fn get_battery() -> String {
+    let (batteries, ac_online) = get_batteries_from(Path::new("/sys/class/power_supply"));
+
+    match batteries.as_slice() {
+        [battery] if !battery.peripheral => battery.format(ac_online),
+        batteries => batteries
+            .iter()
+            .map(|battery| format!("{}: {}", battery.name, battery.format(ac_online)))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fns::TestDir;
+
+    // A power supply in `power_supply_path` with the given files.
+    fn supply(power_supply_path: &Path, name: &str, files: &[(&str, &str)]) {
+        let path = power_supply_path.join(name);
+        fs::create_dir_all(&path).unwrap();
+        for (file, value) in files {
+            fs::write(path.join(file), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn laptop_on_usb_c_with_a_mouse() {
+        let power_supply = TestDir::new("power-supply");
+        supply(&power_supply, "AC", &[("type", "Mains"), ("online", "0")]);
+        supply(
+            &power_supply,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Not charging"),
+                ("capacity", "80"),
+                ("energy_now", "40000000"),
+                ("energy_full", "50000000"),
+                ("energy_full_design", "57000000"),
+                ("power_now", "0"),
+            ],
+        );
+        supply(
+            &power_supply,
+            "hidpp_battery_0",
+            &[
+                ("type", "Battery"),
+                ("scope", "Device"),
+                ("model_name", "MX Master 3"),
+                ("status", "Discharging"),
+                ("capacity_level", "Normal"),
+            ],
+        );
+        supply(
+            &power_supply,
+            "ucsi-source-psy-USBC000:001",
+            &[("type", "USB"), ("online", "1")],
+        );
+
+        let (batteries, ac_online) = get_batteries_from(&power_supply);
+        assert!(ac_online);
+        let batteries: Vec<(String, String)> = batteries
+            .iter()
+            .map(|battery| (battery.name.clone(), battery.format(ac_online)))
+            .collect();
+        assert_eq!(
+            batteries,
+            [
+                (
+                    "BAT0".to_string(),
+                    "80% [not charging, AC, 87% health]".to_string()
+                ),
+                (
+                    "MX Master 3".to_string(),
+                    "normal [discharging]".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn discharging_from_charge() {
+        let power_supply = TestDir::new("power-supply");
+        supply(
+            &power_supply,
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("charge_now", "3000000"),
+                ("charge_full", "4000000"),
+                ("current_now", "1200000"),
+            ],
+        );
+        // A phone charging off the laptop doesn't power it.
+        supply(
+            &power_supply,
+            "usb-phone",
+            &[("type", "USB"), ("scope", "Device"), ("online", "1")],
+        );
+        // Neither a percentage nor a level.
+        supply(
+            &power_supply,
+            "hid-controller-battery",
+            &[
+                ("type", "Battery"),
+                ("scope", "Device"),
+                ("capacity_level", "Unknown"),
+            ],
+        );
+
+        let (batteries, ac_online) = get_batteries_from(&power_supply);
+        assert!(!ac_online);
+        assert_eq!(batteries.len(), 1);
+        assert_eq!(
+            batteries[0].format(ac_online),
+            "75% [discharging, 2h 30m left]"
+        );
+    }
+}
@@ -1,8 +1,7 @@
 use colored::Color;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
+use std::fs;
 
 use crate::fns::home_dir;
 
@@ -12,62 +11,63 @@ pub struct JsonColors {
 }
 
 impl JsonColors {
-    pub fn load_from_file(file_path: &str, overriden: bool) -> Result<Self, String> {
-        let mut file = match File::open(file_path) {
-            Ok(file) => file,
-            Err(err) => return Err(format!("Error opening file: {}", err)),
-        };
-
-        let mut json_data = String::new();
-        match file.read_to_string(&mut json_data) {
-            Ok(_) => (),
-            Err(err) => return Err(format!("Error reading file: {}", err)),
-        }
-
-        match (serde_json::from_str(&json_data), overriden) {
-            (Ok(colors), false) => Ok(colors),
-            (Err(_), _) | (Ok(_), true) => {
-                let default_colors = r#"{"colors":{"color0":"blue","color1":"green","color2":"red","color3":"purple"}}"#;
-                match serde_json::from_str(default_colors) {
-                    Ok(colors) => Ok(colors),
-                    Err(err) => Err(format!("Error parsing default colors: {}", err)),
-                }
-            }
+    pub fn defaults() -> Result<Self, String> {
+        let default_colors =
+            r#"{"colors":{"color0":"blue","color1":"green","color2":"red","color3":"purple"}}"#;
+        match serde_json::from_str(default_colors) {
+            Ok(colors) => Ok(colors),
+            Err(err) => Err(format!("Error parsing default colors: {}", err)),
         }
     }
 
     pub fn get_color_by_section(&self, section: &str) -> Option<Color> {
-        match self.colors.get(section) {
-            Some(color_str) => match color_str.to_ascii_lowercase().as_str() {
-                "green" => Some(Color::Green),
-                "red" => Some(Color::Red),
-                "purple" | "magenta" => Some(Color::Magenta),
-                "yellow" => Some(Color::Yellow),
-                "blue" => Some(Color::Blue),
-                "black" => Some(Color::Black),
-                "white" | _ => Some(Color::White),
-            },
-            None => None,
+        self.colors
+            .get(section)
+            .map(|color_str| parse_color(color_str))
+    }
+}
+
+pub fn parse_color(color_str: &str) -> Color {
+    if let Some(hex) = color_str.strip_prefix('#') {
+        if let (6, Ok(rgb)) = (hex.len(), u32::from_str_radix(hex, 16)) {
+            return Color::TrueColor {
+                r: (rgb >> 16) as u8,
+                g: (rgb >> 8) as u8,
+                b: rgb as u8,
+            };
         }
     }
+
+    match color_str.to_ascii_lowercase().as_str() {
+        "green" => Color::Green,
+        "red" => Color::Red,
+        "purple" | "magenta" => Color::Magenta,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "black" => Color::Black,
+        _ => Color::White,
+    }
 }
 
-pub fn get_color_config(
-    section: String,
+// Only the colors the user actually set, so callers can fall back to their own defaults.
+pub fn load_custom_colors(
     overriden_colors: bool,
     custom_file: Option<String>,
-) -> Color {
-    let mut path =
-        custom_file.unwrap_or_else(|| format!("{}/.config/rsftch/colors.json", home_dir()));
-
-    loop {
-        let colors = JsonColors::load_from_file(&path, overriden_colors)
-            .unwrap_or_else(|_| JsonColors::load_from_file("/dev/null", true).unwrap());
+) -> Option<JsonColors> {
+    if overriden_colors {
+        return None;
+    }
 
-        if let Some(color) = colors.get_color_by_section(section.as_str()) {
-            return color;
-        }
+    let path = custom_file.unwrap_or_else(|| format!("{}/.config/rsftch/colors.json", home_dir()));
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<JsonColors>(&data).ok())
+}
 
-        path = "/dev/null".to_string();
-    }
+/// The color of `section`, from the user's colors when they set it, otherwise the default.
+pub fn get_color_config(section: &str, custom_colors: Option<&JsonColors>) -> Color {
+    custom_colors
+        .and_then(|colors| colors.get_color_by_section(section))
+        .or_else(|| JsonColors::defaults().ok()?.get_color_by_section(section))
+        .unwrap_or(Color::White)
 }
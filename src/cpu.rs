@@ -0,0 +1,751 @@
+use crate::fns::{fill_template, read_trimmed};
+use std::{collections::HashSet, fs, path::Path};
+
+const CPU_PATH: &str = "/sys/devices/system/cpu";
+
+#[derive(Clone, Debug, Default)]
+pub struct CpuDetails {
+    pub cores: Option<usize>,
+    pub threads: Option<usize>,
+    pub sockets: Option<usize>,
+    /// Frequencies in MHz.
+    pub freq: Option<f64>,
+    pub max_freq: Option<f64>,
+    /// Performance and efficiency cores, only set on hybrid CPUs.
+    pub hybrid: Option<(usize, usize)>,
+}
+
+/// Parses a CPU list like "0-3,8-11" into its CPU numbers.
+fn parse_cpu_list(list: &str) -> Vec<usize> {
+    list.trim()
+        .split(',')
+        .filter_map(|range| match range.split_once('-') {
+            Some((start, end)) => Some((start.parse().ok()?..=end.parse().ok()?).collect()),
+            None => Some(vec![range.parse().ok()?]),
+        })
+        .flatten()
+        .collect()
+}
+
+// (package, core) of every online CPU, from sysfs topology or /proc/cpuinfo.
+fn get_topology(cpu_path: &Path, cpuinfo: &str) -> Vec<(usize, (usize, usize))> {
+    let online = read_trimmed(&cpu_path.join("online"))
+        .map(|online| parse_cpu_list(&online))
+        .unwrap_or_default();
+
+    let topology: Vec<(usize, (usize, usize))> = online
+        .iter()
+        .filter_map(|cpu| {
+            let topology = cpu_path.join(format!("cpu{}/topology", cpu));
+            let package = read_trimmed(&topology.join("physical_package_id"))?
+                .parse()
+                .ok()?;
+            let core = read_trimmed(&topology.join("core_id"))?.parse().ok()?;
+            Some((*cpu, (package, core)))
+        })
+        .collect();
+    if !topology.is_empty() {
+        return topology;
+    }
+
+    let mut topology = vec![];
+    let (mut processor, mut package, mut core) = (None, 0, None);
+    for line in cpuinfo.lines().chain([""]) {
+        match line
+            .split_once(':')
+            .map(|(key, value)| (key.trim(), value.trim()))
+        {
+            Some(("processor", value)) => processor = value.parse().ok(),
+            Some(("physical id", value)) => package = value.parse().unwrap_or(0),
+            Some(("core id", value)) => core = value.parse().ok(),
+            None if line.trim().is_empty() => {
+                if let Some(processor) = processor.take() {
+                    topology.push((processor, (package, core.take().unwrap_or(processor))));
+                }
+                package = 0;
+            }
+            _ => {}
+        }
+    }
+    topology
+}
+
+// Highest value of `file` over every cpufreq policy, in MHz.
+fn get_freq(cpu_path: &Path, file: &str) -> Option<f64> {
+    fs::read_dir(cpu_path.join("cpufreq"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| read_trimmed(&entry.path().join(file))?.parse::<f64>().ok())
+        .map(|khz| khz / 1000.0)
+        .reduce(f64::max)
+}
+
+// Intel lists its core types under /sys/devices/cpu_core and cpu_atom, ARM big.LITTLE
+// CPUs give the performance cores a higher cpu_capacity.
+fn get_hybrid(cpu_path: &Path, topology: &[(usize, (usize, usize))]) -> Option<(usize, usize)> {
+    let count_cores = |cpus: &[usize]| {
+        topology
+            .iter()
+            .filter(|(cpu, _)| cpus.contains(cpu))
+            .map(|(_, core)| core)
+            .collect::<HashSet<_>>()
+            .len()
+    };
+
+    let devices = cpu_path.parent()?.parent()?;
+    if let (Some(performance), Some(efficiency)) = (
+        read_trimmed(&devices.join("cpu_core/cpus")),
+        read_trimmed(&devices.join("cpu_atom/cpus")),
+    ) {
+        return Some((
+            count_cores(&parse_cpu_list(&performance)),
+            count_cores(&parse_cpu_list(&efficiency)),
+        ));
+    }
+
+    let capacities: Vec<(usize, u32)> = topology
+        .iter()
+        .filter_map(|(cpu, _)| {
+            let capacity = read_trimmed(&cpu_path.join(format!("cpu{}/cpu_capacity", cpu)))?;
+            Some((*cpu, capacity.parse().ok()?))
+        })
+        .collect();
+    let max_capacity = capacities.iter().map(|(_, capacity)| *capacity).max()?;
+    let (performance, efficiency): (Vec<_>, Vec<_>) = capacities
+        .iter()
+        .partition(|(_, capacity)| *capacity == max_capacity);
+    if efficiency.is_empty() {
+        return None;
+    }
+
+    let cpus = |cpus: Vec<&(usize, u32)>| cpus.iter().map(|(cpu, _)| *cpu).collect::<Vec<_>>();
+    Some((
+        count_cores(&cpus(performance)),
+        count_cores(&cpus(efficiency)),
+    ))
+}
+
+fn get_cpu_details_from(cpu_path: &Path, cpuinfo: &str) -> CpuDetails {
+    let topology = get_topology(cpu_path, cpuinfo);
+    let nonzero = |count: usize| (count > 0).then_some(count);
+
+    let cpuinfo_mhz = cpuinfo
+        .lines()
+        .filter_map(|line| line.strip_prefix("cpu MHz")?.split(':').nth(1))
+        .filter_map(|mhz| mhz.trim().parse::<f64>().ok())
+        .reduce(f64::max);
+
+    CpuDetails {
+        cores: nonzero(
+            topology
+                .iter()
+                .map(|(_, core)| core)
+                .collect::<HashSet<_>>()
+                .len(),
+        ),
+        threads: nonzero(topology.len()),
+        sockets: nonzero(
+            topology
+                .iter()
+                .map(|(_, (package, _))| package)
+                .collect::<HashSet<_>>()
+                .len(),
+        ),
+        freq: get_freq(cpu_path, "scaling_cur_freq").or(cpuinfo_mhz),
+        max_freq: get_freq(cpu_path, "cpuinfo_max_freq"),
+        hybrid: get_hybrid(cpu_path, &topology),
+    }
+}
+
+pub fn get_cpu_details(cpuinfo: &str) -> CpuDetails {
+    get_cpu_details_from(Path::new(CPU_PATH), cpuinfo)
+}
+
+fn format_freq(mhz: f64) -> String {
+    format!("{:.2} GHz", mhz / 1000.0)
+}
+
+/// Fills in a template like "{model} [({topology})] [@ {max_freq}]". Placeholders
+/// without a value are left empty, along with the optional parts they're in.
+pub fn format_cpu(template: &str, model: &str, details: &CpuDetails) -> String {
+    let count = |count: Option<usize>| count.map(|count| count.to_string()).unwrap_or_default();
+    // "8C/16T", or only the counts that could be read.
+    let topology = [
+        details.cores.map(|cores| format!("{}C", cores)),
+        details.threads.map(|threads| format!("{}T", threads)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<String>>()
+    .join("/");
+
+    let placeholders = [
+        ("{model}", model.to_string()),
+        ("{topology}", topology),
+        ("{cores}", count(details.cores)),
+        ("{threads}", count(details.threads)),
+        ("{sockets}", count(details.sockets)),
+        ("{freq}", details.freq.map(format_freq).unwrap_or_default()),
+        (
+            "{max_freq}",
+            details.max_freq.map(format_freq).unwrap_or_default(),
+        ),
+        (
+            "{hybrid}",
+            details
+                .hybrid
+                .map(|(performance, efficiency)| format!("{}P+{}E", performance, efficiency))
+                .unwrap_or_default(),
+        ),
+    ];
+    fill_template(template, &placeholders)
+}
+
+const DEVICE_TREE_PATH: &str = "/proc/device-tree";
+
+const ARM_IMPLEMENTERS: [(u32, &str); 13] = [
+    (0x41, "ARM"),
+    (0x42, "Broadcom"),
+    (0x43, "Cavium"),
+    (0x46, "Fujitsu"),
+    (0x48, "HiSilicon"),
+    (0x4e, "NVIDIA"),
+    (0x50, "APM"),
+    (0x51, "Qualcomm"),
+    (0x53, "Samsung"),
+    (0x56, "Marvell"),
+    (0x61, "Apple"),
+    (0x69, "Intel"),
+    (0xc0, "Ampere"),
+];
+
+const ARM_PARTS: [(u32, u32, &str); 44] = [
+    (0x41, 0xc07, "Cortex-A7"),
+    (0x41, 0xc08, "Cortex-A8"),
+    (0x41, 0xc09, "Cortex-A9"),
+    (0x41, 0xc0d, "Cortex-A12"),
+    (0x41, 0xc0e, "Cortex-A17"),
+    (0x41, 0xc0f, "Cortex-A15"),
+    (0x41, 0xd01, "Cortex-A32"),
+    (0x41, 0xd03, "Cortex-A53"),
+    (0x41, 0xd04, "Cortex-A35"),
+    (0x41, 0xd05, "Cortex-A55"),
+    (0x41, 0xd07, "Cortex-A57"),
+    (0x41, 0xd08, "Cortex-A72"),
+    (0x41, 0xd09, "Cortex-A73"),
+    (0x41, 0xd0a, "Cortex-A75"),
+    (0x41, 0xd0b, "Cortex-A76"),
+    (0x41, 0xd0c, "Neoverse-N1"),
+    (0x41, 0xd0d, "Cortex-A77"),
+    (0x41, 0xd40, "Neoverse-V1"),
+    (0x41, 0xd41, "Cortex-A78"),
+    (0x41, 0xd44, "Cortex-X1"),
+    (0x41, 0xd46, "Cortex-A510"),
+    (0x41, 0xd47, "Cortex-A710"),
+    (0x41, 0xd48, "Cortex-X2"),
+    (0x41, 0xd49, "Neoverse-N2"),
+    (0x41, 0xd4d, "Cortex-A715"),
+    (0x41, 0xd4e, "Cortex-X3"),
+    (0x41, 0xd4f, "Neoverse-V2"),
+    (0x41, 0xd80, "Cortex-A520"),
+    (0x41, 0xd81, "Cortex-A720"),
+    (0x41, 0xd82, "Cortex-X4"),
+    (0x51, 0x001, "Oryon"),
+    (0x51, 0x800, "Kryo Gold"),
+    (0x51, 0x801, "Kryo Silver"),
+    (0x51, 0x802, "Kryo Gold"),
+    (0x51, 0x803, "Kryo Silver"),
+    (0x51, 0x804, "Kryo Gold"),
+    (0x51, 0x805, "Kryo Silver"),
+    (0x61, 0x022, "M1 Icestorm"),
+    (0x61, 0x023, "M1 Firestorm"),
+    (0x61, 0x024, "M1 Pro Icestorm"),
+    (0x61, 0x025, "M1 Pro Firestorm"),
+    (0x61, 0x032, "M2 Blizzard"),
+    (0x61, 0x033, "M2 Avalanche"),
+    (0xc0, 0xac3, "Ampere-1"),
+];
+
+// Vendor prefixes of device tree `compatible` strings and RISC-V `uarch` lines.
+const DT_VENDORS: [(&str, &str); 18] = [
+    ("allwinner", "Allwinner"),
+    ("amlogic", "Amlogic"),
+    ("apple", "Apple"),
+    ("brcm", "Broadcom"),
+    ("fsl", "NXP"),
+    ("hisilicon", "HiSilicon"),
+    ("mediatek", "MediaTek"),
+    ("nvidia", "NVIDIA"),
+    ("nxp", "NXP"),
+    ("qcom", "Qualcomm"),
+    ("rockchip", "Rockchip"),
+    ("samsung", "Samsung"),
+    ("sifive", "SiFive"),
+    ("sophgo", "Sophgo"),
+    ("spacemit", "SpacemiT"),
+    ("starfive", "StarFive"),
+    ("thead", "T-Head"),
+    ("ti", "TI"),
+];
+
+const S390_MACHINES: [(&str, &str); 8] = [
+    ("2817", "z196"),
+    ("2827", "zEC12"),
+    ("2964", "z13"),
+    ("3906", "z14"),
+    ("3907", "z14 ZR1"),
+    ("8561", "z15"),
+    ("8562", "z15 T02"),
+    ("3931", "z16"),
+];
+
+// Key/value pairs of every processor block in /proc/cpuinfo, and of the lines before
+// the first block on architectures that put shared fields there.
+fn cpuinfo_blocks(cpuinfo: &str) -> Vec<Vec<(String, String)>> {
+    cpuinfo
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim().to_lowercase(), value.trim().to_string()))
+                .collect::<Vec<(String, String)>>()
+        })
+        .filter(|block| !block.is_empty())
+        .collect()
+}
+
+fn cpuinfo_value<'a>(blocks: &'a [Vec<(String, String)>], key: &str) -> Option<&'a str> {
+    blocks
+        .iter()
+        .flatten()
+        .find(|(found, value)| found == key && !value.is_empty())
+        .map(|(_, value)| value.as_str())
+}
+
+/// "sifive,u74-mc" to "SiFive U74-MC".
+fn pretty_compatible(compatible: &str) -> String {
+    let Some((vendor, name)) = compatible.split_once(',') else {
+        return compatible.to_uppercase();
+    };
+    let vendor = DT_VENDORS
+        .iter()
+        .find(|(prefix, _)| *prefix == vendor)
+        .map(|(_, vendor)| vendor.to_string())
+        .unwrap_or_else(|| vendor.to_string());
+    format!("{} {}", vendor, name.to_uppercase())
+}
+
+fn read_device_tree(device_tree: &Path, file: &str) -> Vec<String> {
+    fs::read(device_tree.join(file))
+        .map(|content| {
+            content
+                .split(|byte| *byte == 0)
+                .map(|entry| String::from_utf8_lossy(entry).trim().to_string())
+                .filter(|entry| !entry.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// The SoC is the last, least specific `compatible` entry, the board coming first.
+fn device_tree_soc(device_tree: &Path) -> Option<String> {
+    read_device_tree(device_tree, "compatible")
+        .iter()
+        .rev()
+        .find(|compatible| !compatible.ends_with("-platform"))
+        .map(|compatible| pretty_compatible(compatible))
+}
+
+// Counts of each core name in order of appearance, like "4x Cortex-A76 + 4x Cortex-A55".
+fn count_cores(names: Vec<String>) -> Option<String> {
+    let mut counts: Vec<(String, usize)> = vec![];
+    for name in names {
+        match counts.iter_mut().find(|(found, _)| *found == name) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((name, 1)),
+        }
+    }
+
+    if counts.is_empty() {
+        return None;
+    }
+    Some(
+        counts
+            .iter()
+            .map(|(name, count)| format!("{}x {}", count, name))
+            .collect::<Vec<String>>()
+            .join(" + "),
+    )
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+fn arm_core_name(implementer: u32, part: u32) -> String {
+    let vendor = ARM_IMPLEMENTERS
+        .iter()
+        .find(|(id, _)| *id == implementer)
+        .map(|(_, vendor)| vendor.to_string())
+        .unwrap_or_else(|| format!("Implementer 0x{:02x}", implementer));
+
+    match ARM_PARTS
+        .iter()
+        .find(|(id, part_id, _)| *id == implementer && *part_id == part)
+    {
+        Some((0x41, _, name)) => name.to_string(),
+        Some((_, _, name)) => format!("{} {}", vendor, name),
+        None => format!("{} part 0x{:03x}", vendor, part),
+    }
+}
+
+fn with_soc(soc: Option<String>, cores: Option<String>) -> Option<String> {
+    match (soc, cores) {
+        (Some(soc), Some(cores)) => Some(format!("{} ({})", soc, cores)),
+        (soc, cores) => soc.or(cores),
+    }
+}
+
+fn get_cpu_model_from(cpuinfo: &str, device_tree: &Path) -> String {
+    let blocks = cpuinfo_blocks(cpuinfo);
+    let value = |key: &str| cpuinfo_value(&blocks, key);
+
+    // ARM's "model name" is a generic "ARMv7 Processor rev 3 (v7l)", the cores are
+    // decoded from their implementer and part numbers instead.
+    let arm_cores: Vec<String> = blocks
+        .iter()
+        .filter_map(|block| {
+            let field = |key: &str| {
+                block
+                    .iter()
+                    .find(|(found, _)| found == key)
+                    .and_then(|(_, value)| parse_hex(value))
+            };
+            Some(arm_core_name(field("cpu implementer")?, field("cpu part")?))
+        })
+        .collect();
+    if !arm_cores.is_empty() {
+        let soc = device_tree_soc(device_tree).or(value("hardware").map(String::from));
+        return with_soc(soc, count_cores(arm_cores)).unwrap_or_default();
+    }
+
+    // RISC-V, falling back to the base ISA without its long list of extensions.
+    let riscv_cores: Vec<String> = blocks
+        .iter()
+        .filter_map(|block| {
+            let field = |key: &str| block.iter().find(|(found, _)| found == key);
+            match (field("uarch"), field("isa")) {
+                (Some((_, uarch)), _) => Some(pretty_compatible(uarch)),
+                (None, Some((_, isa))) => Some(isa.split('_').next()?.to_string()),
+                (None, None) => None,
+            }
+        })
+        .collect();
+    if !riscv_cores.is_empty() {
+        return with_soc(device_tree_soc(device_tree), count_cores(riscv_cores))
+            .unwrap_or_default();
+    }
+
+    // s390 only has the machine type, e.g. "processor 0: version = FF, ..., machine = 8561".
+    if value("vendor_id") == Some("IBM/S390") {
+        let machine = blocks
+            .iter()
+            .flatten()
+            .find_map(|(key, value)| match key.as_str() {
+                "machine" => Some(value.as_str()),
+                _ => value.split(',').find_map(|field| {
+                    field
+                        .trim()
+                        .strip_prefix("machine")?
+                        .trim()
+                        .strip_prefix('=')
+                }),
+            })
+            .map(str::trim);
+        return match machine {
+            Some(machine) => match S390_MACHINES.iter().find(|(id, _)| *id == machine) {
+                Some((_, name)) => format!("IBM {} ({})", name, machine),
+                None => format!("IBM/S390 {}", machine),
+            },
+            None => "IBM/S390".to_string(),
+        };
+    }
+
+    // POWER's `cpu` line looks like "POWER9 (raw), altivec supported".
+    ["model name", "cpu model", "cpu", "hardware", "chip type"]
+        .iter()
+        .find_map(|key| value(key))
+        .map(|model| {
+            let model = model.split(',').next().unwrap_or(model);
+            model.trim_end_matches(" (raw)").trim().to_string()
+        })
+        .or_else(|| device_tree_soc(device_tree))
+        .or_else(|| read_device_tree(device_tree, "model").into_iter().next())
+        .unwrap_or_default()
+}
+
+/// The CPU model name, decoded in the way the architecture's /proc/cpuinfo needs.
+pub fn get_cpu_model(cpuinfo: &str) -> String {
+    get_cpu_model_from(cpuinfo, Path::new(DEVICE_TREE_PATH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fns::TestDir;
+
+    const RASPBERRY_PI_5: &str = "processor\t: 0
+BogoMIPS\t: 108.00
+Features\t: fp asimd evtstrm aes pmull sha1 sha2 crc32 atomics fphp asimdhp cpuid asimdrdm lrcpc dcpop asimddp
+CPU implementer\t: 0x41
+CPU architecture: 8
+CPU variant\t: 0x4
+CPU part\t: 0xd0b
+CPU revision\t: 1
+
+processor\t: 1
+BogoMIPS\t: 108.00
+Features\t: fp asimd evtstrm aes pmull sha1 sha2 crc32 atomics fphp asimdhp cpuid asimdrdm lrcpc dcpop asimddp
+CPU implementer\t: 0x41
+CPU architecture: 8
+CPU variant\t: 0x4
+CPU part\t: 0xd0b
+CPU revision\t: 1
+
+Revision\t: d04170
+Serial\t\t: 2c8f5e0d1a2b3c4d
+Model\t\t: Raspberry Pi 5 Model B Rev 1.0
+";
+
+    const RASPBERRY_PI_2: &str = "processor\t: 0
+model name\t: ARMv7 Processor rev 5 (v7l)
+BogoMIPS\t: 38.40
+Features\t: half thumb fastmult vfp edsp neon vfpv3 tls vfpv4 idiva idivt vfpd32 lpae evtstrm
+CPU implementer\t: 0x41
+CPU architecture: 7
+CPU variant\t: 0x0
+CPU part\t: 0xc07
+CPU revision\t: 5
+
+Hardware\t: BCM2835
+Revision\t: a21041
+";
+
+    // A big.LITTLE RK3588 with its efficiency cores listed first.
+    const RK3588: &str = "processor\t: 0
+CPU implementer\t: 0x41
+CPU part\t: 0xd05
+
+processor\t: 1
+CPU implementer\t: 0x41
+CPU part\t: 0xd05
+
+processor\t: 2
+CPU implementer\t: 0x41
+CPU part\t: 0xd0b
+
+processor\t: 3
+CPU implementer\t: 0x41
+CPU part\t: 0xd0b
+";
+
+    const APPLE_M1: &str = "processor\t: 0
+BogoMIPS\t: 48.00
+CPU implementer\t: 0x61
+CPU architecture: 8
+CPU variant\t: 0x1
+CPU part\t: 0x022
+CPU revision\t: 1
+";
+
+    const VISIONFIVE_2: &str = "processor\t: 0
+hart\t\t: 1
+isa\t\t: rv64imafdc_zicntr_zicsr_zifencei_zihpm_zba_zbb
+mmu\t\t: sv39
+uarch\t\t: sifive,u74-mc
+mvendorid\t: 0x489
+marchid\t\t: 0x8000000000000007
+mimpid\t\t: 0x4210427
+
+processor\t: 1
+hart\t\t: 2
+isa\t\t: rv64imafdc_zicntr_zicsr_zifencei_zihpm_zba_zbb
+mmu\t\t: sv39
+uarch\t\t: sifive,u74-mc
+mvendorid\t: 0x489
+marchid\t\t: 0x8000000000000007
+mimpid\t\t: 0x4210427
+";
+
+    const QEMU_RISCV: &str = "processor\t: 0
+hart\t\t: 0
+isa\t\t: rv64imafdch_zicbom_zicboz_zicntr_zicsr_zifencei_zihintpause_zihpm_zba_zbb_zbs
+mmu\t\t: sv57
+mvendorid\t: 0x0
+marchid\t\t: 0x0
+mimpid\t\t: 0x0
+";
+
+    const POWER9: &str = "processor\t: 0
+cpu\t\t: POWER9 (raw), altivec supported
+clock\t\t: 2166.000000MHz
+revision\t: 2.2 (pvr 004e 1202)
+
+processor\t: 1
+cpu\t\t: POWER9 (raw), altivec supported
+clock\t\t: 2166.000000MHz
+revision\t: 2.2 (pvr 004e 1202)
+
+timebase\t: 512000000
+platform\t: PowerNV
+model\t\t: 9006-22P
+machine\t\t: PowerNV 9006-22P
+firmware\t: OPAL
+MMU\t\t: Radix
+";
+
+    const S390: &str = "vendor_id       : IBM/S390
+# processors    : 2
+bogomips per cpu: 3241.00
+max thread id   : 0
+features\t: esan3 zarch stfle msa ldisp eimm dfp edat etf3eh highgprs te vx vxd vxe gs vxe2 vxp sort dflt sie
+facilities      : 0 1 2 3 4 6 7 8 9 10 12 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 30 31 32
+cache0          : level=1 type=Data scope=Private size=128K line_size=256 associativity=8
+processor 0: version = 00,  identification = 3B8B38,  machine = 8561
+processor 1: version = 00,  identification = 3B8B38,  machine = 8561
+
+cpu number      : 0
+version         : 00
+identification  : 3B8B38
+machine         : 8561
+";
+
+    // An empty device tree directory, like on machines without one.
+    fn no_device_tree() -> TestDir {
+        TestDir::new("no-device-tree")
+    }
+
+    // A device tree with the given `compatible` entries and `model`, NUL-terminated like
+    // the kernel writes them.
+    fn device_tree(name: &str, compatible: &[&str], model: Option<&str>) -> TestDir {
+        let path = TestDir::new(name);
+        let compatible: String = compatible
+            .iter()
+            .map(|entry| format!("{}\0", entry))
+            .collect();
+        fs::write(path.join("compatible"), compatible).unwrap();
+        if let Some(model) = model {
+            fs::write(path.join("model"), format!("{}\0", model)).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn arm_cores_are_decoded_from_implementer_and_part() {
+        assert_eq!(
+            get_cpu_model_from(RASPBERRY_PI_5, &no_device_tree()),
+            "2x Cortex-A76"
+        );
+        assert_eq!(
+            get_cpu_model_from(RK3588, &no_device_tree()),
+            "2x Cortex-A55 + 2x Cortex-A76"
+        );
+        assert_eq!(
+            get_cpu_model_from(APPLE_M1, &no_device_tree()),
+            "1x Apple M1 Icestorm"
+        );
+    }
+
+    #[test]
+    fn arm_soc_comes_from_the_device_tree_or_hardware_field() {
+        let raspberry_pi = device_tree(
+            "pi5",
+            &["raspberrypi,5-model-b", "brcm,bcm2712"],
+            Some("Raspberry Pi 5 Model B Rev 1.0"),
+        );
+        assert_eq!(
+            get_cpu_model_from(RASPBERRY_PI_5, &raspberry_pi),
+            "Broadcom BCM2712 (2x Cortex-A76)"
+        );
+
+        let rock_5b = device_tree("rock5b", &["radxa,rock-5b", "rockchip,rk3588"], None);
+        assert_eq!(
+            get_cpu_model_from(RK3588, &rock_5b),
+            "Rockchip RK3588 (2x Cortex-A55 + 2x Cortex-A76)"
+        );
+
+        assert_eq!(
+            get_cpu_model_from(RASPBERRY_PI_2, &no_device_tree()),
+            "BCM2835 (1x Cortex-A7)"
+        );
+    }
+
+    #[test]
+    fn riscv_cores_come_from_uarch_or_isa() {
+        let visionfive = device_tree(
+            "visionfive2",
+            &["starfive,visionfive-2-v1.3b", "starfive,jh7110"],
+            None,
+        );
+        assert_eq!(
+            get_cpu_model_from(VISIONFIVE_2, &visionfive),
+            "StarFive JH7110 (2x SiFive U74-MC)"
+        );
+        assert_eq!(
+            get_cpu_model_from(QEMU_RISCV, &no_device_tree()),
+            "1x rv64imafdch"
+        );
+    }
+
+    #[test]
+    fn power_cpu_line_is_cleaned_up() {
+        assert_eq!(get_cpu_model_from(POWER9, &no_device_tree()), "POWER9");
+    }
+
+    #[test]
+    fn s390_machine_types_are_named() {
+        assert_eq!(
+            get_cpu_model_from(S390, &no_device_tree()),
+            "IBM z15 (8561)"
+        );
+        assert_eq!(
+            get_cpu_model_from(&S390.replace("8561", "9175"), &no_device_tree()),
+            "IBM/S390 9175"
+        );
+    }
+
+    #[test]
+    fn device_tree_is_the_last_resort() {
+        let soc_only = device_tree("soc-only", &["vendor,board", "amlogic,g12b"], None);
+        assert_eq!(get_cpu_model_from("", &soc_only), "Amlogic G12B");
+
+        let model_only = device_tree("model-only", &[], Some("Custom Board v2"));
+        assert_eq!(get_cpu_model_from("", &model_only), "Custom Board v2");
+    }
+
+    #[test]
+    fn x86_model_name_is_kept() {
+        let cpuinfo = "processor\t: 0\nvendor_id\t: AuthenticAMD\nmodel name\t: AMD Ryzen 7 5800X 8-Core Processor\n";
+        assert_eq!(
+            get_cpu_model_from(cpuinfo, &no_device_tree()),
+            "AMD Ryzen 7 5800X 8-Core Processor"
+        );
+    }
+
+    #[test]
+    fn topology_only_has_the_counts_that_were_read() {
+        let details = CpuDetails {
+            threads: Some(16),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_cpu("{model} [({topology})]", "Ryzen", &details),
+            "Ryzen (16T)"
+        );
+        assert_eq!(
+            format_cpu("{model} [({topology})]", "Ryzen", &CpuDetails::default()),
+            "Ryzen"
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use crate::process::get_process;
+use crate::version::cached_version;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// Process name (as in /proc/<pid>/comm, cut to 15 characters), WM name, and the flag
+// that prints its version. WMs without a flag could start a second instance if asked.
+const WMS: [(&str, &str, Option<&str>); 34] = [
+    ("sway", "Sway", Some("--version")),
+    ("Hyprland", "Hyprland", Some("--version")),
+    ("kwin_wayland", "KWin", Some("--version")),
+    ("kwin_x11", "KWin", Some("--version")),
+    ("gnome-shell", "Mutter", Some("--version")),
+    ("mutter", "Mutter", Some("--version")),
+    ("cosmic-comp", "cosmic-comp", None),
+    ("muffin", "Muffin", Some("--version")),
+    ("cinnamon", "Muffin", None),
+    ("marco", "Marco", Some("--version")),
+    ("xfwm4", "Xfwm4", Some("--version")),
+    ("budgie-wm", "Budgie WM", None),
+    ("openbox", "Openbox", Some("--version")),
+    ("i3", "i3", Some("--version")),
+    ("bspwm", "bspwm", Some("-v")),
+    ("dwm", "dwm", Some("-v")),
+    ("awesome", "awesome", Some("--version")),
+    ("herbstluftwm", "herbstluftwm", Some("--version")),
+    ("qtile", "Qtile", None),
+    ("xmonad-x86_64-l", "xmonad", None),
+    ("xmonad", "xmonad", None),
+    ("fluxbox", "Fluxbox", Some("-version")),
+    ("icewm", "IceWM", Some("--version")),
+    ("fvwm3", "FVWM", Some("--version")),
+    ("jwm", "JWM", Some("-v")),
+    ("spectrwm", "spectrwm", None),
+    ("leftwm", "LeftWM", Some("--version")),
+    ("enlightenment", "Enlightenment", Some("-version")),
+    ("river", "river", Some("-version")),
+    ("wayfire", "Wayfire", Some("--version")),
+    ("niri", "niri", Some("--version")),
+    ("labwc", "labwc", Some("--version")),
+    ("weston", "Weston", Some("--version")),
+    ("dwl", "dwl", Some("-v")),
+];
+
+// Session processes of desktop environments, for when the XDG variables aren't set.
+const DE_PROCESSES: [(&str, &str); 9] = [
+    ("plasmashell", "KDE Plasma"),
+    ("gnome-session-b", "GNOME"),
+    ("xfce4-session", "Xfce"),
+    ("cinnamon-sessio", "Cinnamon"),
+    ("mate-session", "MATE"),
+    ("lxqt-session", "LXQt"),
+    ("lxsession", "LXDE"),
+    ("budgie-panel", "Budgie"),
+    ("cosmic-session", "COSMIC"),
+];
+
+// XDG_CURRENT_DESKTOP values that don't read well as they are.
+const DE_NAMES: [(&str, &str); 6] = [
+    ("kde", "KDE Plasma"),
+    ("xfce", "Xfce"),
+    ("x-cinnamon", "Cinnamon"),
+    ("budgie", "Budgie"),
+    ("lxqt", "LXQt"),
+    ("cosmic", "COSMIC"),
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct Desktop {
+    pub de: Option<String>,
+    pub wm: Option<String>,
+    pub wm_version: Option<String>,
+    pub protocol: Option<&'static str>,
+}
+
+impl Desktop {
+    /// "KWin 6.0.5 (Wayland)"
+    pub fn format_wm(&self) -> String {
+        let Some(wm) = &self.wm else {
+            return String::new();
+        };
+        let mut formatted = wm.clone();
+        if let Some(version) = &self.wm_version {
+            formatted.push_str(&format!(" {}", version));
+        }
+        if let Some(protocol) = self.protocol {
+            formatted.push_str(&format!(" ({})", protocol));
+        }
+        formatted
+    }
+
+    /// "KDE Plasma (KWin 6.0.5, Wayland)", or only the WM part without a DE.
+    pub fn format(&self) -> String {
+        let Some(de) = &self.de else {
+            return self.format_wm();
+        };
+
+        let wm = self.wm.as_ref().map(|wm| match &self.wm_version {
+            Some(version) => format!("{} {}", wm, version),
+            None => wm.clone(),
+        });
+        let details: Vec<String> = wm
+            .into_iter()
+            .chain(self.protocol.map(String::from))
+            .collect();
+
+        if details.is_empty() {
+            de.clone()
+        } else {
+            format!("{} ({})", de, details.join(", "))
+        }
+    }
+}
+
+/// Names and executables of the processes running as us, in our login session or in no
+/// session at all (compositors started as user services) when `XDG_SESSION_ID` is set.
+/// Other users' and other sessions' desktops running on the same machine are left out.
+fn get_processes() -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return vec![];
+    };
+    let uid = unsafe { libc::getuid() };
+    let session = env::var("XDG_SESSION_ID")
+        .ok()
+        .filter(|session| !session.is_empty());
+
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .filter_map(get_process)
+        .filter(|process| process.uid() == Some(uid))
+        .filter(|process| match (&session, process.session()) {
+            (Some(ours), Some(theirs)) => *ours == theirs,
+            _ => true,
+        })
+        .map(|process| {
+            let exe = process.exe().unwrap_or_default();
+            (process.comm, exe)
+        })
+        .collect()
+}
+
+// The executable to ask for the version. It can't be read for processes that aren't
+// dumpable, like WMs with file capabilities, so then the name is looked up in `PATH`.
+fn wm_executable(exe: &Path, comm: &str) -> Option<PathBuf> {
+    if !exe.as_os_str().is_empty() {
+        return Some(exe.to_path_buf());
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(comm))
+        .find(|path| path.is_file())
+}
+
+fn de_from_env() -> Option<String> {
+    let de = [
+        "XDG_CURRENT_DESKTOP",
+        "XDG_SESSION_DESKTOP",
+        "DESKTOP_SESSION",
+    ]
+    .iter()
+    .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()))?;
+
+    // Lists like "ubuntu:GNOME" put the distribution's flavour first.
+    let de = de
+        .split(':')
+        .find(|de| !["ubuntu", "pop", "unity"].contains(&de.to_lowercase().as_str()))
+        .unwrap_or(&de)
+        .to_string();
+    Some(
+        DE_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&de))
+            .map(|(_, display)| display.to_string())
+            .unwrap_or(de),
+    )
+}
+
+fn get_protocol() -> Option<&'static str> {
+    match env::var("XDG_SESSION_TYPE").as_deref() {
+        Ok("wayland") => return Some("Wayland"),
+        Ok("x11") => return Some("X11"),
+        _ => {}
+    }
+    if env::var("WAYLAND_DISPLAY").is_ok() {
+        Some("Wayland")
+    } else if env::var("DISPLAY").is_ok() {
+        Some("X11")
+    } else {
+        None
+    }
+}
+
+pub fn get_desktop() -> Desktop {
+    let processes = get_processes();
+    let running = |name: &str| processes.iter().find(|(comm, _)| comm == name);
+
+    let wm = WMS
+        .iter()
+        .find_map(|(comm, name, flag)| Some((running(comm)?, *name, *flag)));
+    let wm_version =
+        wm.and_then(|((comm, exe), _, flag)| cached_version(&wm_executable(exe, comm)?, flag?));
+    let wm = wm.map(|(_, name, _)| name.to_string());
+
+    let de = de_from_env()
+        .or_else(|| {
+            DE_PROCESSES
+                .iter()
+                .find(|(comm, _)| running(comm).is_some())
+                .map(|(_, de)| de.to_string())
+        })
+        // Standalone WMs set the XDG variables to their own name.
+        .filter(|de| {
+            !WMS.iter().any(|(comm, name, _)| {
+                de.eq_ignore_ascii_case(comm) || de.eq_ignore_ascii_case(name)
+            })
+        });
+
+    // A WM named in the XDG variables that isn't running (or not under that name).
+    let wm = wm.or_else(|| {
+        let name = de_from_env()?;
+        WMS.iter()
+            .find(|(comm, wm, _)| name.eq_ignore_ascii_case(comm) || name.eq_ignore_ascii_case(wm))
+            .map(|(_, wm, _)| wm.to_string())
+    });
+
+    Desktop {
+        de,
+        wm,
+        wm_version,
+        protocol: get_protocol(),
+    }
+}
@@ -0,0 +1,250 @@
+use crate::size::SizeFormat;
+use std::{ffi::CString, fs, mem};
+
+// Filesystems that don't take up any disk space, or are read-only images like snaps,
+// left out of the `all` list.
+const PSEUDO_FILESYSTEMS: [&str; 34] = [
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "erofs",
+    "fuse.gvfsd-fuse",
+    "fuse.lxcfs",
+    "fuse.portal",
+    "fuse.snapfuse",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nfsd",
+    "nsfs",
+    "overlay",
+    "proc",
+    "pstore",
+    "ramfs",
+    "rootfs",
+    "rpc_pipefs",
+    "securityfs",
+    "selinuxfs",
+    "smackfs",
+    "squashfs",
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+    "zonefs",
+];
+
+// Used, total and available bytes, from `statvfs`.
+type Usage = (u64, u64, u64);
+
+#[derive(Clone, Debug)]
+struct Mount {
+    device: String,
+    path: String,
+    fs_type: String,
+}
+
+/// Mount paths escape spaces and such as octal, e.g. "/mnt/my\040disk".
+fn unescape(path: &str) -> String {
+    let mut unescaped = vec![];
+    let mut bytes = path.bytes();
+    while let Some(byte) = bytes.next() {
+        let octal: Vec<u8> = bytes.clone().take(3).collect();
+        match std::str::from_utf8(&octal).map(|octal| u8::from_str_radix(octal, 8)) {
+            Ok(Ok(escaped)) if byte == b'\\' && octal.len() == 3 => {
+                unescaped.push(escaped);
+                bytes.nth(2);
+            }
+            _ => unescaped.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&unescaped).to_string()
+}
+
+fn get_mounts() -> Vec<Mount> {
+    let mounts = fs::read_to_string("/proc/self/mounts")
+        .or_else(|_| fs::read_to_string("/proc/mounts"))
+        .unwrap_or_default();
+
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(Mount {
+                device: unescape(fields.next()?),
+                path: unescape(fields.next()?),
+                fs_type: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Used and total bytes of the filesystem `path` is on, plus what unprivileged users
+/// can still use of it.
+fn statvfs(path: &str) -> Option<Usage> {
+    let path = CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    let fragment = stat.f_frsize as u64;
+    let total = stat.f_blocks as u64 * fragment;
+    let free = stat.f_bfree as u64 * fragment;
+    let available = stat.f_bavail as u64 * fragment;
+    Some((total.saturating_sub(free), total, available))
+}
+
+// The last mount whose path `path` is in, later mounts hiding earlier ones.
+fn mount_of<'a>(mounts: &'a [Mount], path: &str) -> Option<&'a Mount> {
+    mounts.iter().rev().find(|mount| {
+        path == mount.path
+            || mount.path == "/"
+            || path
+                .strip_prefix(&mount.path)
+                .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// The mountpoints shown by default: `/`, and `/home` when it's a separate filesystem.
+fn default_mountpoints(mounts: &[Mount]) -> Vec<String> {
+    let mut mountpoints = vec!["/".to_string()];
+    let root = mount_of(mounts, "/").map(|root| root.device.as_str());
+    let home = mount_of(mounts, "/home");
+    if home.is_some_and(|home| home.path == "/home" && Some(home.device.as_str()) != root) {
+        mountpoints.push("/home".to_string());
+    }
+    mountpoints
+}
+
+// Every real filesystem with its usage, only once when a device is mounted in multiple
+// places. Loop devices are left out too, they're mostly snaps and other mounted images.
+// Each one is only stat'd once, a stale network mount can take long to answer.
+fn all_mountpoints(mounts: &[Mount]) -> Vec<(String, Usage)> {
+    let mut devices: Vec<&str> = vec![];
+    mounts
+        .iter()
+        .filter(|mount| !PSEUDO_FILESYSTEMS.contains(&mount.fs_type.as_str()))
+        .filter(|mount| !mount.device.starts_with("/dev/loop"))
+        .filter_map(|mount| Some((mount, statvfs(&mount.path)?)))
+        .filter(|(mount, (_, total, _))| {
+            if *total == 0 || devices.contains(&mount.device.as_str()) {
+                return false;
+            }
+            devices.push(&mount.device);
+            true
+        })
+        .map(|(mount, usage)| (mount.path.clone(), usage))
+        .collect()
+}
+
+/// The `disk` module, one row per mountpoint in `mountpoints`, `/` and `/home` when it's
+/// empty, or every real filesystem for `["all"]`.
+pub fn get_disk_usage(mountpoints: &[String], size: SizeFormat) -> String {
+    let mounts = get_mounts();
+    let with_usage = |mountpoints: Vec<String>| -> Vec<(String, Usage)> {
+        mountpoints
+            .into_iter()
+            .filter_map(|mountpoint| {
+                let usage = statvfs(&mountpoint)?;
+                Some((mountpoint, usage))
+            })
+            .collect()
+    };
+    let usages = match mountpoints {
+        [] => with_usage(default_mountpoints(&mounts)),
+        [all] if all == "all" => all_mountpoints(&mounts),
+        mountpoints => with_usage(mountpoints.to_vec()),
+    };
+
+    usages
+        .into_iter()
+        .filter_map(|(mountpoint, (used, total, available))| {
+            if total == 0 {
+                return None;
+            }
+            // Like df, the reserved blocks don't count towards the percentage.
+            let percent = (used * 100).div_ceil((used + available).max(1));
+            let fs_type = mount_of(&mounts, &mountpoint)
+                .map(|mount| format!(" ({})", mount.fs_type))
+                .unwrap_or_default();
+            Some(format!(
+                "{}{}: {} ({}%)",
+                mountpoint,
+                fs_type,
+                size.usage(used, total),
+                percent
+            ))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mounts(mounts: &[(&str, &str)]) -> Vec<Mount> {
+        mounts
+            .iter()
+            .map(|(device, path)| Mount {
+                device: device.to_string(),
+                path: path.to_string(),
+                fs_type: "ext4".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unescape_octal() {
+        assert_eq!(unescape("/mnt/my\\040disk"), "/mnt/my disk");
+        assert_eq!(
+            unescape("/mnt/tab\\011and\\134slash"),
+            "/mnt/tab\tand\\slash"
+        );
+        assert_eq!(unescape("/mnt/caf\\303\\251"), "/mnt/café");
+        // Not three octal digits, so left as it is.
+        assert_eq!(unescape("/mnt/a\\09b"), "/mnt/a\\09b");
+        assert_eq!(unescape("/mnt/end\\04"), "/mnt/end\\04");
+        assert_eq!(unescape("/"), "/");
+    }
+
+    #[test]
+    fn mount_of_path() {
+        let mounts = mounts(&[
+            ("/dev/nvme0n1p2", "/"),
+            ("/dev/nvme0n1p3", "/home"),
+            ("/dev/sda1", "/home/user/data"),
+            ("/dev/sdb1", "/mnt"),
+            ("server:/export", "/mnt"),
+        ]);
+        let device = |path| mount_of(&mounts, path).map(|mount| mount.device.as_str());
+        assert_eq!(device("/home"), Some("/dev/nvme0n1p3"));
+        assert_eq!(device("/home/user"), Some("/dev/nvme0n1p3"));
+        assert_eq!(device("/home/user/data/photos"), Some("/dev/sda1"));
+        // Not in /home, only starting with it.
+        assert_eq!(device("/homework"), Some("/dev/nvme0n1p2"));
+        // The later mount hides the earlier one.
+        assert_eq!(device("/mnt/backup"), Some("server:/export"));
+        assert!(mount_of(&[], "/").is_none());
+    }
+
+    #[test]
+    fn home_only_when_separate() {
+        let separate = mounts(&[("/dev/nvme0n1p2", "/"), ("/dev/nvme0n1p3", "/home")]);
+        assert_eq!(default_mountpoints(&separate), ["/", "/home"]);
+
+        // A btrfs subvolume or bind mount of the root filesystem.
+        let same = mounts(&[("/dev/nvme0n1p2", "/"), ("/dev/nvme0n1p2", "/home")]);
+        assert_eq!(default_mountpoints(&same), ["/"]);
+
+        let none = mounts(&[("/dev/nvme0n1p2", "/")]);
+        assert_eq!(default_mountpoints(&none), ["/"]);
+    }
+}
@@ -0,0 +1,326 @@
+use serde_json::Value;
+use std::{
+    env, fs,
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+// i3's IPC message type for listing outputs, which sway implements too.
+const I3_GET_OUTPUTS: u32 = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    pub connector: String,
+    pub width: u64,
+    pub height: u64,
+    pub refresh: Option<f64>,
+    pub scale: Option<f64>,
+}
+
+impl Monitor {
+    /// "DP-1: 2560x1440 @ 144Hz (1.5x)", leaving out what isn't known.
+    pub fn format(&self) -> String {
+        let mut monitor = format!("{}: {}x{}", self.connector, self.width, self.height);
+        if let Some(refresh) = self.refresh {
+            monitor.push_str(&format!(" @ {}Hz", refresh.round()));
+        }
+        if let Some(scale) = self.scale.filter(|scale| *scale != 1.0) {
+            monitor.push_str(&format!(" ({}x)", scale));
+        }
+        monitor
+    }
+}
+
+fn connect(socket: &Path) -> Option<UnixStream> {
+    let stream = UnixStream::connect(socket).ok()?;
+    stream.set_read_timeout(Some(Duration::from_secs(1))).ok()?;
+    stream
+        .set_write_timeout(Some(Duration::from_secs(1)))
+        .ok()?;
+    Some(stream)
+}
+
+fn get_sway_monitors() -> Option<Vec<Monitor>> {
+    let mut stream = connect(Path::new(&env::var("SWAYSOCK").ok()?))?;
+
+    // "i3-ipc", payload length and message type, with an empty payload.
+    let mut message = b"i3-ipc".to_vec();
+    message.extend(0u32.to_ne_bytes());
+    message.extend(I3_GET_OUTPUTS.to_ne_bytes());
+    stream.write_all(&message).ok()?;
+
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header).ok()?;
+    let length = u32::from_ne_bytes(header[6..10].try_into().ok()?) as usize;
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).ok()?;
+
+    let outputs: Vec<Value> = serde_json::from_slice(&payload).ok()?;
+    Some(
+        outputs
+            .iter()
+            .filter(|output| output["active"].as_bool().unwrap_or(false))
+            .filter_map(|output| {
+                let mode = &output["current_mode"];
+                Some(Monitor {
+                    connector: output["name"].as_str()?.to_string(),
+                    width: mode["width"].as_u64()?,
+                    height: mode["height"].as_u64()?,
+                    // In mHz.
+                    refresh: mode["refresh"].as_f64().map(|refresh| refresh / 1000.0),
+                    scale: output["scale"].as_f64(),
+                })
+            })
+            .collect(),
+    )
+}
+
+fn get_hyprland_monitors() -> Option<Vec<Monitor>> {
+    let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+    // Moved from /tmp to the runtime directory in Hyprland 0.40.
+    let sockets = env::var("XDG_RUNTIME_DIR")
+        .map(|runtime| PathBuf::from(runtime).join("hypr"))
+        .into_iter()
+        .chain([PathBuf::from("/tmp/hypr")])
+        .map(|hypr| hypr.join(&signature).join(".socket.sock"));
+    let mut stream = sockets.into_iter().find_map(|socket| connect(&socket))?;
+
+    stream.write_all(b"j/monitors").ok()?;
+    let mut response = vec![];
+    stream.read_to_end(&mut response).ok()?;
+
+    let monitors: Vec<Value> = serde_json::from_slice(&response).ok()?;
+    Some(
+        monitors
+            .iter()
+            .filter(|monitor| !monitor["disabled"].as_bool().unwrap_or(false))
+            .filter_map(|monitor| {
+                Some(Monitor {
+                    connector: monitor["name"].as_str()?.to_string(),
+                    width: monitor["width"].as_u64()?,
+                    height: monitor["height"].as_u64()?,
+                    refresh: monitor["refreshRate"].as_f64(),
+                    scale: monitor["scale"].as_f64(),
+                })
+            })
+            .collect(),
+    )
+}
+
+// The outputs `xrandr --query` lists as connected and turned on.
+fn parse_xrandr(query: &str) -> Vec<Monitor> {
+    let mut monitors: Vec<Monitor> = vec![];
+    // Whether the indented modes that follow belong to the last monitor.
+    let mut in_monitor = false;
+    for line in query.lines() {
+        // The current mode is marked with a "*" in the indented list under each output.
+        if line.starts_with(' ') {
+            let Some(monitor) = monitors.last_mut().filter(|_| in_monitor) else {
+                continue;
+            };
+            if monitor.refresh.is_none() {
+                monitor.refresh = line
+                    .split_whitespace()
+                    .find(|rate| rate.contains('*'))
+                    .and_then(|rate| rate.trim_end_matches(['*', '+']).parse().ok());
+            }
+            continue;
+        }
+
+        in_monitor = false;
+        let mut fields = line.split_whitespace();
+        let connector = fields.next().unwrap_or_default();
+        if fields.next() != Some("connected") {
+            continue;
+        }
+        let Some((width, height)) = fields
+            .find(|field| field.contains('x') && field.contains('+'))
+            .and_then(|geometry| geometry.split('+').next()?.split_once('x'))
+        else {
+            continue;
+        };
+        monitors.push(Monitor {
+            connector: connector.to_string(),
+            width: width.parse().unwrap_or(0),
+            height: height.parse().unwrap_or(0),
+            refresh: None,
+            scale: None,
+        });
+        in_monitor = true;
+    }
+    monitors
+}
+
+fn get_xrandr_monitors() -> Option<Vec<Monitor>> {
+    let output = Command::new("xrandr").arg("--query").output().ok()?;
+    let monitors = parse_xrandr(&String::from_utf8_lossy(&output.stdout));
+    (!monitors.is_empty()).then_some(monitors)
+}
+
+/// Refresh rate of the preferred timing in an EDID, if it is `width` by `height`.
+fn edid_refresh(edid: &[u8], width: u64, height: u64) -> Option<f64> {
+    let timing = edid.get(54..72)?;
+    let pixel_clock = u16::from_le_bytes([timing[0], timing[1]]) as f64 * 10_000.0;
+    let h_active = timing[2] as u64 | ((timing[4] as u64 >> 4) << 8);
+    let h_blank = timing[3] as u64 | ((timing[4] as u64 & 0xf) << 8);
+    let v_active = timing[5] as u64 | ((timing[7] as u64 >> 4) << 8);
+    let v_blank = timing[6] as u64 | ((timing[7] as u64 & 0xf) << 8);
+
+    if pixel_clock == 0.0 || (h_active, v_active) != (width, height) {
+        return None;
+    }
+    Some(pixel_clock / ((h_active + h_blank) * (v_active + v_blank)) as f64)
+}
+
+/// Monitors on the connected connectors of `drm_path`. The kernel only knows the
+/// preferred mode, not what the compositor picked.
+fn get_drm_monitors_from(drm_path: &Path) -> Vec<Monitor> {
+    let Ok(entries) = fs::read_dir(drm_path) else {
+        return vec![];
+    };
+
+    let mut connectors: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // "card0-DP-1" to "DP-1", leaving out the cards themselves.
+            let (card, connector) = name.split_once('-')?;
+            card.starts_with("card")
+                .then(|| (connector.to_string(), entry.path()))
+        })
+        .collect();
+    connectors.sort();
+
+    connectors
+        .into_iter()
+        .filter(|(_, path)| {
+            fs::read_to_string(path.join("status")).is_ok_and(|status| status.trim() == "connected")
+        })
+        .filter_map(|(connector, path)| {
+            let modes = fs::read_to_string(path.join("modes")).ok()?;
+            let (width, height) = modes.lines().next()?.split_once('x')?;
+            let width = width.parse().ok()?;
+            // Interlaced modes end in "i".
+            let height = height.trim_end_matches('i').parse().ok()?;
+            let refresh = fs::read(path.join("edid"))
+                .ok()
+                .and_then(|edid| edid_refresh(&edid, width, height));
+            Some(Monitor {
+                connector,
+                width,
+                height,
+                refresh,
+                scale: None,
+            })
+        })
+        .collect()
+}
+
+/// The `res` module, one row per monitor. Asks the compositor first, as only it knows
+/// the mode and scale in use, then xrandr on X11 and the kernel otherwise. xrandr isn't
+/// asked on Wayland, where it only sees XWayland's made-up outputs.
+pub fn get_res() -> String {
+    let x11 = env::var("DISPLAY").is_ok() && env::var("WAYLAND_DISPLAY").is_err();
+    let monitors = get_sway_monitors()
+        .or_else(get_hyprland_monitors)
+        .filter(|monitors| !monitors.is_empty())
+        .or_else(|| x11.then(get_xrandr_monitors).flatten())
+        .or_else(|| {
+            let monitors = get_drm_monitors_from(Path::new("/sys/class/drm"));
+            (!monitors.is_empty()).then_some(monitors)
+        })
+        .unwrap_or_default();
+
+    monitors
+        .iter()
+        .map(Monitor::format)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fns::TestDir;
+
+    const XRANDR: &str = "\
+Screen 0: minimum 320 x 200, current 4480 x 1440, maximum 16384 x 16384
+DP-1 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 597mm x 336mm
+   2560x1440    143.97*+  59.95
+   1920x1080     60.00
+HDMI-1 disconnected (normal left inverted right x axis y axis)
+DP-2 connected (normal left inverted right x axis y axis)
+   3840x2160     60.00 +  30.00*
+HDMI-2 connected 1920x1080+2560+0 (normal left inverted right x axis y axis) 527mm x 296mm
+   1920x1080     60.00 +  74.97*   50.00
+";
+
+    #[test]
+    fn xrandr_outputs() {
+        let monitor = |connector: &str, width, height, refresh| Monitor {
+            connector: connector.to_string(),
+            width,
+            height,
+            refresh: Some(refresh),
+            scale: None,
+        };
+        // DP-2 is connected but turned off, so its modes aren't HDMI-2's or DP-1's.
+        assert_eq!(
+            parse_xrandr(XRANDR),
+            [
+                monitor("DP-1", 2560, 1440, 143.97),
+                monitor("HDMI-2", 1920, 1080, 74.97),
+            ]
+        );
+        assert_eq!(parse_xrandr(""), []);
+    }
+
+    // The base block of an EDID, with 1920x1080 at 60Hz as the preferred timing.
+    fn edid() -> Vec<u8> {
+        let mut edid = vec![0u8; 128];
+        edid[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+        edid[54..62].copy_from_slice(&[0x02, 0x3a, 0x80, 0x18, 0x71, 0x38, 0x2d, 0x40]);
+        edid
+    }
+
+    #[test]
+    fn edid_preferred_refresh() {
+        assert_eq!(edid_refresh(&edid(), 1920, 1080), Some(60.0));
+        // The preferred timing isn't the mode the kernel listed first.
+        assert_eq!(edid_refresh(&edid(), 2560, 1440), None);
+        assert_eq!(edid_refresh(&edid()[..64], 1920, 1080), None);
+
+        let mut no_clock = edid();
+        no_clock[54..56].copy_from_slice(&[0, 0]);
+        assert_eq!(edid_refresh(&no_clock, 1920, 1080), None);
+    }
+
+    #[test]
+    fn drm_connectors() {
+        let drm = TestDir::new("drm-connectors");
+        let connector = |name: &str, status: &str, modes: &str| {
+            let path = drm.join(name);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("status"), format!("{}\n", status)).unwrap();
+            fs::write(path.join("modes"), modes).unwrap();
+            path
+        };
+        fs::create_dir_all(drm.join("card0")).unwrap();
+        fs::write(
+            connector("card0-HDMI-A-1", "connected", "1920x1080\n1280x720\n").join("edid"),
+            edid(),
+        )
+        .unwrap();
+        connector("card0-DP-1", "disconnected", "");
+        connector("card0-eDP-1", "connected", "2256x1504\n");
+
+        let monitors: Vec<String> = get_drm_monitors_from(&drm)
+            .iter()
+            .map(Monitor::format)
+            .collect();
+        assert_eq!(monitors, ["HDMI-A-1: 1920x1080 @ 60Hz", "eDP-1: 2256x1504"]);
+    }
+}
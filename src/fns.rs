@@ -1,8 +1,14 @@
+use crate::cpu::{format_cpu, get_cpu_details, get_cpu_model};
+use crate::gpu::Gpu;
+use crate::size::SizeFormat;
+use crate::temps::{device_temp, get_sensors, TempUnit};
 use rayon::prelude::*;
 use std::{
+    collections::HashMap,
     env,
     fs::{self, read_to_string, File},
-    io::{BufRead, BufReader, Error, Read},
+    io::{BufRead, BufReader, Error},
+    mem,
     path::Path,
     process::{Command, Stdio},
     sync::{Arc, Mutex},
@@ -12,20 +18,30 @@ use std::{
 pub fn help() {
     println!(
         r#"Usage: rsftch [OPTION...] [OVERRIDE] [MARGIN] [CONFIG FILE(s)] [INFO]
+       rsftch logo convert IMAGE [--width COLUMNS] [--colors 1-6] [--chars ascii|blocks]
+                                 [--name NAME] [--match IDS] [--output FILE]
         
       -h, --help, --usage         Bring up this menu.
       -v, --version               Print version number.
       -o, --override              Overrides distribution, affects ASCII and "distro" info. Running without
                                   an argument prints all possible options.
       -m, --margin                Add margin to the info sections, default 1.
+      -l, --logo-size             Logo size to use, "small", "large" or "none". By default the largest
+                                  one that fits in the terminal is picked.
       -c, --color-config          Specify another color config file, to be used instead of the default one.
       -i, --info-config           Specify another info config file, to be used instead of the default one.
           --ignore-color-config   Ignores the custom color config and uses the default one.
           --ignore-info-config    Ignores the custom info config and uses the default one.
           --ignore-config         Ignores both configs and uses the default ones.
+          --redact                Masks the hostname, username, IP and MAC addresses, Wi-Fi network
+                                  names and serial numbers, for sharing the output.
           --info                  Only prints the value of the following arguments info, for example
                                   `rsftch --info distro` would output: "EndeavourOS".
 
+    logo convert turns a PNG into a logo file, written to ~/.config/rsftch/logos/ID.txt by default, ID
+    being NAME in lowercase with dashes for spaces. The logo is shown on systems with that os-release ID,
+    or one of the comma separated IDs given with --match.
+
     Info config is located at:  ~/.config/rsftch/info.json
     Color config is located at: ~/.config/rsftch/colors.json"#
     );
@@ -38,12 +54,13 @@ pub fn whoami() -> String {
     String::from_utf8_lossy(&output.stdout).trim().to_string()
 }
 
-pub fn home_dir() -> &'static str {
-    if let Some(home) = option_env!("HOME") {
-        return home;
+// Read when rsftch runs, not when it's built, so packaged binaries find the user's config.
+pub fn home_dir() -> String {
+    if let Ok(home) = env::var("HOME") {
+        home
     } else {
         eprintln!("Couldn't find home directory, are you on a Unix-like operating system? \nIf you are, use the \"--ignore-config\" flag, or the \"--(info/color)-config\" flag and set \nan other file to act as the config file.");
-        return "";
+        String::new()
     }
 }
 
@@ -71,14 +88,15 @@ pub fn get_timezone() -> String {
     String::new()
 }
 
-pub fn get_cpu_temp() -> String {
+pub fn get_cpu_temp() -> Option<f64> {
     #[cfg(target_os = "linux")]
     {
-        fs::read_to_string("/sys/class/thermal/thermal_zone0/temp")
-            .ok()
-            .and_then(|temp_str| temp_str.trim().parse::<f64>().ok())
-            .map(|temp| format!("({:.1}°C)", temp / 1000.0))
-            .unwrap_or_default()
+        crate::temps::cpu_temp(&get_sensors()).or_else(|| {
+            fs::read_to_string("/sys/class/thermal/thermal_zone0/temp")
+                .ok()
+                .and_then(|temp_str| temp_str.trim().parse::<f64>().ok())
+                .map(|temp| temp / 1000.0)
+        })
     }
 
     #[cfg(target_os = "netbsd")]
@@ -95,50 +113,39 @@ pub fn get_cpu_temp() -> String {
                     .and_then(|line| line.split(':').nth(1))
                     .map(|s| s.split_whitespace().next().unwrap_or(""))
                     .and_then(|temp_str| temp_str.parse::<f64>().ok())
-                    .map(|temp| format!("({:.1}°C)", temp))
             })
-            .unwrap_or_default()
     }
 }
 
-fn get_gpu_temp() -> String {
+// The proprietary NVIDIA driver doesn't register with hwmon, so its temperatures come from
+// `nvidia-smi`, keyed by PCI bus id.
+fn get_nvidia_temps() -> Vec<(String, f64)> {
+    Command::new("nvidia-smi")
+        .arg("--query-gpu=pci.bus_id,temperature.gpu")
+        .arg("--format=csv,noheader")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|output_str| {
+            output_str
+                .lines()
+                .filter_map(|line| {
+                    let (bus_id, temp) = line.split_once(',')?;
+                    Some((bus_id.trim().to_lowercase(), temp.trim().parse().ok()?))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn get_gpu_temp() -> Option<f64> {
     #[cfg(target_os = "linux")]
     {
-        Command::new("nvidia-smi")
-            .arg("--query-gpu=temperature.gpu")
-            .arg("--format=csv,noheader")
-            .output()
-            .ok()
-            .and_then(|output| String::from_utf8(output.stdout).ok())
-            .and_then(|temp_str| {
-                temp_str
-                    .lines()
-                    .next()
-                    .and_then(|s| s.trim().parse::<f64>().ok())
-            })
-            .map(|temp| format!("({:.1}°C)", temp))
-            .unwrap_or_else(|| {
-                Command::new("sensors")
-                    .output()
-                    .ok()
-                    .and_then(|output| String::from_utf8(output.stdout).ok())
-                    .and_then(|output_str| {
-                        output_str
-                            .lines()
-                            .find(|line| {
-                                line.contains("temp1:")
-                                    || line.contains("edge:")
-                                    || line.contains("junction:")
-                                    || line.contains("mem:")
-                            })
-                            .and_then(|line| line.split_whitespace().nth(1))
-                            .and_then(|temp_str| {
-                                temp_str.trim_end_matches("°C").parse::<f64>().ok()
-                            })
-                            .map(|temp| format!("({:.1}°C)", temp))
-                    })
-                    .unwrap_or_default()
-            })
+        get_sensors()
+            .iter()
+            .find(|sensor| sensor.kind == crate::temps::SensorKind::Gpu)
+            .map(|sensor| sensor.celsius)
+            .or_else(|| get_nvidia_temps().first().map(|(_, temp)| *temp))
     }
 
     #[cfg(target_os = "netbsd")]
@@ -155,13 +162,87 @@ fn get_gpu_temp() -> String {
                     .and_then(|line| line.split(':').nth(1))
                     .map(|s| s.split_whitespace().next().unwrap_or(""))
                     .and_then(|temp_str| temp_str.parse::<f64>().ok())
-                    .map(|temp| format!("({:.1}°C)", temp))
             })
-            .unwrap_or_default()
     }
 }
 
-pub fn get_gpu_info() -> Result<String, Error> {
+// Temperature of each GPU, from its hwmon chip or `nvidia-smi`.
+pub fn get_gpu_temps_of(gpus: &[Gpu]) -> Vec<Option<f64>> {
+    let sensors = get_sensors();
+    let nvidia_temps = if gpus
+        .iter()
+        .any(|gpu| gpu.driver.as_deref() == Some("nvidia"))
+    {
+        get_nvidia_temps()
+    } else {
+        vec![]
+    };
+
+    gpus.iter()
+        .map(|gpu| {
+            device_temp(&sensors, &gpu.device_path).or_else(|| {
+                let slot = gpu.pci_slot.as_ref()?.to_lowercase();
+                nvidia_temps
+                    .iter()
+                    .find(|(bus_id, _)| bus_id.ends_with(&slot))
+                    .map(|(_, temp)| *temp)
+            })
+        })
+        .collect()
+}
+
+/// The `gputemp` module, naming each GPU when there's more than one. `temps` are those of
+/// `gpus`, from [`get_gpu_temps_of`].
+pub fn get_gpu_temps(gpus: &[Gpu], temps: &[Option<f64>], unit: TempUnit) -> String {
+    if gpus.len() <= 1 {
+        let temp = match temps.first() {
+            Some(temp) => *temp,
+            None => get_gpu_temp(),
+        };
+        return temp.map(|temp| unit.format(temp)).unwrap_or_default();
+    }
+
+    gpus.iter()
+        .zip(temps)
+        .filter_map(|(gpu, temp)| Some(format!("{}: {}", gpu.name, unit.format((*temp)?))))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The `gpu` module, with each GPU's temperature from `temps` if `temp_unit` is given.
+pub fn get_gpu_info(
+    gpus: &[Gpu],
+    temps: &[Option<f64>],
+    temp_unit: Option<TempUnit>,
+    size: SizeFormat,
+) -> Result<String, Error> {
+    if gpus.is_empty() {
+        return get_lspci_gpu_info(temp_unit);
+    }
+
+    Ok(gpus
+        .iter()
+        .zip(temps)
+        .map(|(gpu, temp)| {
+            let mut info = format!("{} {}", gpu.vendor, gpu.name).trim().to_string();
+            if let Some(vram) = gpu.vram {
+                info.push_str(&format!(" ({})", size.format(vram)));
+            }
+            let details = gpu.details();
+            if !details.is_empty() {
+                info.push_str(&format!(" {}", details));
+            }
+            if let (Some(unit), Some(temp)) = (temp_unit, temp) {
+                info.push_str(&format!(" ({})", unit.format(*temp)));
+            }
+            info
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+// Used where there's no /sys/class/drm to read from.
+fn get_lspci_gpu_info(temp_unit: Option<TempUnit>) -> Result<String, Error> {
     let output = Command::new("lspci").arg("-nnk").output()?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -194,60 +275,26 @@ pub fn get_gpu_info() -> Result<String, Error> {
                 "GPU name not found",
             ))? + start_index;
             let gpu_name = &line[start_index..end_index];
-            return Ok(format!("{} {} {}", prefix, gpu_name.trim(), get_gpu_temp()));
+            let temp = temp_unit
+                .and_then(|unit| Some(format!(" ({})", unit.format(get_gpu_temp()?))))
+                .unwrap_or_default();
+            return Ok(format!("{} {}{}", prefix, gpu_name.trim(), temp));
         }
     }
 
     Err(Error::new(std::io::ErrorKind::NotFound, "GPU not found"))
 }
 
-pub fn get_disk_usage() -> String {
-    let output_str = match Command::new("df").arg("-h").output() {
-        Ok(output) if output.status.success() => {
-            String::from_utf8(output.stdout).unwrap_or_default()
-        }
-        _ => return String::new(),
-    };
-
-    let line = output_str.lines().find(|line| line.starts_with('/'));
-
-    if let Some(line) = line {
-        let parts: Vec<_> = line.split_whitespace().collect();
-        if parts.len() >= 5 {
-            let filesystem = parts[0];
-            let used = parts[2];
-            let size = parts[1];
-            let capacity = parts[4];
-            return format!("({}) {} / {} ({})", filesystem, used, size, capacity);
-        }
-    }
-
-    String::new()
-}
-
-pub fn get_cpu_info() -> String {
+pub fn get_cpu_info(template: &str, temp_unit: Option<TempUnit>) -> String {
     let cpuinfo = read_to_string("/proc/cpuinfo").expect("Failed to read /proc/cpuinfo");
-    let mut cpu = String::new();
-
-    for line in cpuinfo.lines() {
-        let parts: Vec<&str> = line.split(": ").map(|s| s.trim()).collect();
-        if parts.len() == 2 {
-            match parts[0] {
-                "model name" | "Hardware" | "Processor" | "^cpu model" | "chip type"
-                | "^cpu type" => {
-                    cpu = parts[1].to_string();
-                    break;
-                }
-                _ => {}
-            }
-        }
-    }
+    let cpu = get_cpu_model(&cpuinfo);
 
-    format!(
-        "{}{}",
-        &cpu.split('@').next().unwrap_or_default(),
-        get_cpu_temp()
-    )
+    let model = cpu.split('@').next().unwrap_or_default().trim();
+    let cpu = format_cpu(template, model, &get_cpu_details(&cpuinfo));
+    match temp_unit.and_then(|unit| Some(unit.format(get_cpu_temp()?))) {
+        Some(temp) => format!("{} ({})", cpu, temp),
+        None => cpu,
+    }
 }
 
 fn get_package_managers() -> Vec<&'static str> {
@@ -583,30 +630,6 @@ pub fn get_packages() -> String {
     total_packages.to_string()
 }
 
-pub fn get_res() -> String {
-    let output = match Command::new("xrandr").arg("--query").output() {
-        Ok(out) => out,
-        Err(_err) => return String::new(),
-    };
-
-    String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .filter_map(|line| {
-            if let Some(index) = line.find(" connected") {
-                let line = &line[index + 1..];
-                if let Some(resolution) = line.split_whitespace().find(|s| s.contains('x')) {
-                    Some(resolution.split('+').next().unwrap_or("").to_string())
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<String>>()
-        .join(", ")
-}
-
 pub fn get_uptime() -> Result<String, Error> {
     let file = File::open("/proc/uptime").expect("Failed to open /proc/uptime");
     let mut reader = BufReader::new(file);
@@ -647,19 +670,35 @@ fn format_duration(duration: Duration) -> String {
     uptime_string.trim_end_matches(", ").to_string()
 }
 
-fn search_file(custom_paths: Vec<&'static str>, search_variable: &str) -> Option<String> {
+fn search_file(custom_paths: Vec<&'static str>) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+
     for path in custom_paths.iter() {
         if let Ok(content) = fs::read_to_string(path) {
-            for line in content.lines() {
-                if line.starts_with(search_variable) {
-                    if let Some(name) = line.split('=').nth(1) {
-                        return Some(name.trim_matches('"').to_string());
-                    }
-                }
+            for (key, value) in parse_os_release(&content) {
+                values.entry(key).or_insert(value);
             }
         }
     }
-    None
+
+    values
+}
+
+/// Parses os-release style `KEY="value"` lines, keeping the first value of each key.
+pub fn parse_os_release(content: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    for line in content.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            values
+                .entry(key.trim().to_string())
+                .or_insert_with(|| value.trim().trim_matches(['"', '\'']).to_string());
+        }
+    }
+    values
+}
+
+pub fn get_os_release() -> HashMap<String, String> {
+    search_file(vec!["/etc/os-release", "/etc/lsb-release"])
 }
 
 pub fn get_os_release_pretty_name(
@@ -670,110 +709,182 @@ pub fn get_os_release_pretty_name(
         return overriden_ascii;
     }
 
-    search_file(vec!["/etc/os-release", "/etc/lsb-release"], identifier)
+    get_os_release().remove(identifier)
 }
 
-pub fn get_wm() -> String {
-    if env::var("DISPLAY").is_err() {
-        return String::new();
+pub fn uname_s(overriden_ascii: Option<String>) -> String {
+    if overriden_ascii.is_some() {
+        return overriden_ascii.unwrap_or_default();
     }
+    let output = Command::new("uname")
+        .arg("-s")
+        .output()
+        .expect("uname failed -s");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+pub fn uname_n() -> String {
+    let output = Command::new("uname")
+        .arg("-n")
+        .output()
+        .expect("uname failed -n");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+pub fn get_winsize() -> Option<libc::winsize> {
+    let mut size: libc::winsize = unsafe { mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0 && size.ws_row > 0).then_some(size)
+}
+
+/// Replaces each placeholder in `template` with its value. Text in square brackets is
+/// optional: it's shown without the brackets when every placeholder in it has a value, and
+/// left out otherwise, so "{model}[ ({cores} cores)]" is just the model without a core count.
+pub fn fill_template(template: &str, placeholders: &[(&str, String)]) -> String {
+    let (filled, _, _) = fill_segment(template, placeholders, 0);
+    filled.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
 
-    for env_var in &[
-        "XDG_SESSION_DESKTOP",
-        "XDG_CURRENT_DESKTOP",
-        "DESKTOP_SESSION",
-    ] {
-        if let Ok(de) = env::var(env_var) {
-            return de;
+// Fills `template` up to the `]` closing the current segment, returning the text, whether
+// every placeholder in it had a value, and what's left after the segment.
+fn fill_segment<'a>(
+    mut template: &'a str,
+    placeholders: &[(&str, String)],
+    depth: usize,
+) -> (String, bool, &'a str) {
+    let mut filled = String::new();
+    let mut complete = true;
+
+    while let Some(c) = template.chars().next() {
+        if c == ']' && depth > 0 {
+            return (filled, complete, &template[1..]);
         }
-    }
 
-    let path = format!("{}/.xinitrc", env::var("HOME").unwrap_or_default());
-    if let Ok(mut file) = File::open(&path) {
-        let mut buf = String::new();
-        if file.read_to_string(&mut buf).is_ok() {
-            if let Some(last_line) = buf.lines().last() {
-                let last_word = last_line.split(' ').last().unwrap_or("");
-                return last_word.to_string();
+        if c == '[' {
+            let (segment, segment_complete, rest) =
+                fill_segment(&template[1..], placeholders, depth + 1);
+            if segment_complete {
+                filled.push_str(&segment);
             }
+            template = rest;
+        } else if let Some((placeholder, value)) = placeholders
+            .iter()
+            .find(|(placeholder, _)| template.starts_with(placeholder))
+        {
+            complete &= !value.is_empty();
+            filled.push_str(value);
+            template = &template[placeholder.len()..];
+        } else {
+            filled.push(c);
+            template = &template[c.len_utf8()..];
         }
     }
-    String::new()
+
+    (filled, complete, template)
 }
 
-pub fn get_mem() -> String {
-    let bytes_to_gib = |bytes| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+/// The trimmed contents of a file, usually one from sysfs, or `None` when it's missing or empty.
+pub fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+        .filter(|content| !content.is_empty())
+}
 
-    let parse_meminfo_value = |line: &str| {
-        line.split_whitespace()
-            .nth(1)
-            .unwrap_or("0")
-            .parse()
-            .unwrap_or(0)
-    };
+/// "[a, b, c]" for the details shown after a GPU or drive, or nothing when there are none.
+pub fn bracketed<'a>(details: impl IntoIterator<Item = &'a str>) -> String {
+    let details: Vec<&str> = details.into_iter().collect();
+    if details.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", details.join(", "))
+    }
+}
 
-    if let Ok(file) = File::open("/proc/meminfo") {
-        let reader = BufReader::new(file);
-        let mut mem_total: u64 = 0;
-        let mut mem_free: u64 = 0;
-
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if line.starts_with("MemTotal:") {
-                    mem_total = parse_meminfo_value(&line);
-                } else if line.starts_with("MemFree:") {
-                    mem_free = parse_meminfo_value(&line);
-                }
-            }
-        }
+pub fn expand_home(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("{}/{}", home_dir(), rest),
+        None => path.to_string(),
+    }
+}
 
-        let used = mem_total - mem_free;
-        return format!(
-            "{:.2} GiB / {:.2} GiB",
-            bytes_to_gib(used * 1024),
-            bytes_to_gib(mem_total * 1024)
-        );
+// Columns and lines of the terminal, from the tty itself or `COLUMNS` / `LINES`.
+pub fn terminal_size() -> Option<(usize, usize)> {
+    if let Some(size) = get_winsize() {
+        return Some((size.ws_col as usize, size.ws_row as usize));
     }
 
-    String::new()
+    let columns = env::var("COLUMNS").ok()?.trim().parse().ok()?;
+    let lines = env::var("LINES").ok()?.trim().parse().ok()?;
+    Some((columns, lines))
 }
 
-pub fn uname_r() -> String {
-    let output = Command::new("uname")
-        .arg("-r")
-        .output()
-        .expect("uname failed -r");
-    String::from_utf8_lossy(&output.stdout).trim().to_string()
+/// A uniquely named directory under the system temp dir for test fixtures, removed again
+/// when it's dropped.
+#[cfg(test)]
+pub struct TestDir(std::path::PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+    pub fn new(name: &str) -> TestDir {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let path = env::temp_dir().join(format!(
+            "rsftch-test-{}-{}-{}",
+            name,
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        TestDir(path)
+    }
 }
 
-pub fn uname_s(overriden_ascii: Option<String>) -> String {
-    if overriden_ascii.is_some() {
-        return match overriden_ascii {
-            Some(str) => str,
-            None => String::new(),
-        };
+#[cfg(test)]
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
     }
-    let output = Command::new("uname")
-        .arg("-s")
-        .output()
-        .expect("uname failed -s");
-    String::from_utf8_lossy(&output.stdout).trim().to_string()
 }
 
-pub fn uname_n() -> String {
-    let output = Command::new("uname")
-        .arg("-n")
-        .output()
-        .expect("uname failed -n");
-    String::from_utf8_lossy(&output.stdout).trim().to_string()
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
 }
 
-pub fn shell_name() -> String {
-    let shell = env::var("SHELL").expect("SHELL not set");
-    let parts: Vec<&str> = shell.split('/').collect();
-    parts.last().unwrap().to_string()
-}
+#[cfg(test)]
+mod tests {
+    use super::fill_template;
+
+    fn fill(template: &str) -> String {
+        fill_template(
+            template,
+            &[
+                ("{model}", "Ryzen 7".to_string()),
+                ("{cores}", String::new()),
+                ("{threads}", "16".to_string()),
+            ],
+        )
+    }
 
-pub fn get_terminal() -> String {
-    env::var("TERM").unwrap_or("".to_string())
+    #[test]
+    fn optional_parts_are_dropped_when_a_placeholder_is_empty() {
+        assert_eq!(fill("{model} [({cores} cores)]"), "Ryzen 7");
+        assert_eq!(
+            fill("{model} [({threads} threads)]"),
+            "Ryzen 7 (16 threads)"
+        );
+        assert_eq!(fill("{model} [({threads}T[, {cores}C])]"), "Ryzen 7 (16T)");
+    }
+
+    #[test]
+    fn text_outside_optional_parts_is_kept() {
+        assert_eq!(fill("{model} ({cores} cores)"), "Ryzen 7 ( cores)");
+        assert_eq!(fill("{model}] x"), "Ryzen 7] x");
+    }
 }
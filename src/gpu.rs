@@ -0,0 +1,384 @@
+use crate::fns::{bracketed, read_trimmed};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const PCI_IDS_PATHS: [&str; 4] = [
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+    "/usr/local/share/pciids/pci.ids",
+];
+
+// Used when no pci.ids file is installed, or it doesn't know the vendor.
+const VENDORS: [(u16, &str); 12] = [
+    (0x10de, "NVIDIA"),
+    (0x1002, "AMD"),
+    (0x8086, "Intel"),
+    (0x1af4, "Virtio"),
+    (0x15ad, "VMware"),
+    (0x1234, "QEMU"),
+    (0x80ee, "VirtualBox"),
+    (0x1414, "Microsoft"),
+    (0x1a03, "ASPEED"),
+    (0x102b, "Matrox"),
+    (0x5143, "Qualcomm"),
+    (0x1ed5, "Moore Threads"),
+];
+
+// PCI device IDs of AMD's integrated GPUs, from Llano up to Strix Halo, sorted. Every
+// other AMD GPU is a graphics card, however little VRAM it has.
+const AMD_APUS: [(u16, u16); 27] = [
+    (0x1114, 0x1114), // Krackan Point
+    (0x1304, 0x131d), // Kaveri, Godavari
+    (0x13c0, 0x13c0), // Granite Ridge
+    (0x13fe, 0x13fe), // Cyan Skillfish
+    (0x1435, 0x1435), // Aerith
+    (0x143f, 0x143f), // Cyan Skillfish
+    (0x1506, 0x1506), // Mendocino
+    (0x150e, 0x150e), // Strix Point
+    (0x1586, 0x1586), // Strix Halo
+    (0x15bf, 0x15bf), // Phoenix
+    (0x15c8, 0x15c8), // Phoenix 2
+    (0x15d8, 0x15d8), // Picasso
+    (0x15dd, 0x15dd), // Raven
+    (0x15e7, 0x15e7), // Barcelo
+    (0x1636, 0x1638), // Renoir, Cezanne
+    (0x163f, 0x163f), // Van Gogh
+    (0x164c, 0x164e), // Lucienne, Rembrandt, Raphael
+    (0x1681, 0x1681), // Rembrandt
+    (0x1900, 0x1901), // Hawk Point
+    (0x1903, 0x1903), // Hawk Point
+    (0x9640, 0x964f), // Llano
+    (0x9802, 0x980a), // Ontario, Zacate
+    (0x9830, 0x983f), // Kabini
+    (0x9850, 0x985f), // Mullins
+    (0x9874, 0x9877), // Carrizo
+    (0x98e4, 0x98e4), // Stoney
+    (0x9900, 0x99a4), // Trinity, Richland
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GpuKind {
+    Integrated,
+    Discrete,
+    Unknown,
+}
+
+#[derive(Clone, Debug)]
+pub struct Gpu {
+    pub vendor: String,
+    pub name: String,
+    pub vram: Option<u64>,
+    pub driver: Option<String>,
+    pub kind: GpuKind,
+    pub primary: bool,
+    pub pci_slot: Option<String>,
+    pub device_path: PathBuf,
+}
+
+impl Gpu {
+    /// The "[integrated, i915, primary]" part shown after the GPU name.
+    pub fn details(&self) -> String {
+        let kind = match self.kind {
+            GpuKind::Integrated => Some("integrated"),
+            GpuKind::Discrete => Some("discrete"),
+            GpuKind::Unknown => None,
+        };
+
+        bracketed(
+            kind.into_iter()
+                .chain(self.driver.as_deref())
+                .chain(self.primary.then_some("primary")),
+        )
+    }
+}
+
+fn is_amd_apu(device_id: u16) -> bool {
+    AMD_APUS
+        .iter()
+        .any(|(first, last)| (*first..=*last).contains(&device_id))
+}
+
+fn read_hex(path: &Path) -> Option<u16> {
+    u16::from_str_radix(read_trimmed(path)?.trim_start_matches("0x"), 16).ok()
+}
+
+/// Looks up the vendor and device names in the contents of pci.ids, the device name being
+/// shortened to the marketing name in brackets when there is one.
+fn lookup_pci_ids(
+    pci_ids: Option<&str>,
+    vendor_id: u16,
+    device_id: u16,
+) -> (Option<String>, Option<String>) {
+    let Some(pci_ids) = pci_ids else {
+        return (None, None);
+    };
+
+    let vendor_prefix = format!("{:04x}  ", vendor_id);
+    let device_prefix = format!("\t{:04x}  ", device_id);
+    let mut lines = pci_ids
+        .lines()
+        .skip_while(|line| !line.starts_with(&vendor_prefix));
+
+    let Some(vendor) = lines
+        .next()
+        .map(|line| line[vendor_prefix.len()..].to_string())
+    else {
+        return (None, None);
+    };
+    let device = lines
+        .take_while(|line| line.starts_with('\t') || line.starts_with('#'))
+        .find_map(|line| line.strip_prefix(&device_prefix))
+        .map(|device| match (device.find('['), device.rfind(']')) {
+            (Some(start), Some(end)) if start < end => device[start + 1..end].to_string(),
+            _ => device.to_string(),
+        });
+
+    (Some(vendor), device)
+}
+
+fn vendor_name(vendor_id: u16, pci_ids_vendor: Option<String>) -> String {
+    VENDORS
+        .iter()
+        .find(|(id, _)| *id == vendor_id)
+        .map(|(_, name)| name.to_string())
+        .or(pci_ids_vendor)
+        .unwrap_or_else(|| format!("Vendor {:04x}", vendor_id))
+}
+
+fn read_gpu(device_path: &Path, pci_ids: Option<&str>) -> Option<Gpu> {
+    let uevent = read_trimmed(&device_path.join("uevent")).unwrap_or_default();
+    let uevent_value = |key: &str| {
+        uevent
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(|value| value.to_string())
+    };
+
+    let driver = fs::read_link(device_path.join("driver"))
+        .ok()
+        .and_then(|driver| Some(driver.file_name()?.to_string_lossy().to_string()))
+        .or_else(|| uevent_value("DRIVER"));
+    let vram = read_trimmed(&device_path.join("mem_info_vram_total"))
+        .and_then(|vram| vram.parse().ok())
+        .filter(|vram| *vram > 0);
+    // Set on the GPU the firmware used for the boot console.
+    let primary = read_trimmed(&device_path.join("boot_vga")).as_deref() == Some("1");
+
+    // Platform devices, like most ARM GPUs, only have a driver name to go by.
+    let (Some(vendor_id), Some(device_id)) = (
+        read_hex(&device_path.join("vendor")),
+        read_hex(&device_path.join("device")),
+    ) else {
+        return Some(Gpu {
+            vendor: String::new(),
+            name: driver.clone()?,
+            vram,
+            driver,
+            kind: GpuKind::Integrated,
+            primary,
+            pci_slot: None,
+            device_path: device_path.to_path_buf(),
+        });
+    };
+
+    // Intel's integrated GPUs always sit on the first PCI bus, Arc cards never do.
+    let pci_slot = uevent_value("PCI_SLOT_NAME");
+    let on_first_bus = pci_slot
+        .as_ref()
+        .and_then(|slot| Some(slot.split(':').nth(1)? == "00"))
+        .unwrap_or(false);
+    let kind = match vendor_id {
+        0x10de => GpuKind::Discrete,
+        0x8086 if on_first_bus => GpuKind::Integrated,
+        0x8086 => GpuKind::Discrete,
+        0x1002 if is_amd_apu(device_id) => GpuKind::Integrated,
+        0x1002 => GpuKind::Discrete,
+        _ => GpuKind::Unknown,
+    };
+
+    let (pci_ids_vendor, device) = lookup_pci_ids(pci_ids, vendor_id, device_id);
+    Some(Gpu {
+        vendor: vendor_name(vendor_id, pci_ids_vendor),
+        name: device.unwrap_or_else(|| format!("Device {:04x}", device_id)),
+        vram,
+        driver,
+        kind,
+        primary,
+        pci_slot,
+        device_path: device_path.to_path_buf(),
+    })
+}
+
+/// The GPUs behind the `cardN` entries of `drm_path`, each listed once.
+fn get_gpus_from(drm_path: &Path) -> Vec<Gpu> {
+    let Ok(entries) = fs::read_dir(drm_path) else {
+        return vec![];
+    };
+
+    // Only `cardN`, not its connectors (`cardN-DP-1`) or render nodes.
+    let mut cards: Vec<(u32, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let number = name.strip_prefix("card")?.parse().ok()?;
+            Some((number, entry.path().join("device")))
+        })
+        .collect();
+    cards.sort();
+
+    // Read once for all the GPUs, it's over a megabyte.
+    let pci_ids = if cards.is_empty() {
+        None
+    } else {
+        PCI_IDS_PATHS
+            .iter()
+            .find_map(|path| fs::read_to_string(path).ok())
+    };
+
+    let mut seen: Vec<PathBuf> = vec![];
+    cards
+        .into_iter()
+        .filter_map(|(_, device_path)| {
+            let canonical = fs::canonicalize(&device_path).ok()?;
+            if seen.contains(&canonical) {
+                return None;
+            }
+            seen.push(canonical);
+            read_gpu(&device_path, pci_ids.as_deref())
+        })
+        .collect()
+}
+
+pub fn get_gpus() -> Vec<Gpu> {
+    get_gpus_from(Path::new("/sys/class/drm"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fns::TestDir;
+    use std::os::unix::fs::symlink;
+
+    const PCI_IDS: &str = "\
+# List of PCI ID's
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+\t73bf  Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]
+\t\t1002 0e3a  Radeon RX 6900 XT
+\t73df  Navi 22 [Radeon RX 6700/6700 XT/6750 XT / 6800M/6850M XT]
+10de  NVIDIA Corporation
+# Ampere
+\t2504  GA106 [GeForce RTX 3060 Lite Hash Rate]
+\t2520  GA106M
+8086  Intel Corporation
+";
+
+    #[test]
+    fn pci_ids_lookup() {
+        let lookup = |vendor_id, device_id| lookup_pci_ids(Some(PCI_IDS), vendor_id, device_id);
+        assert_eq!(
+            lookup(0x1002, 0x73bf),
+            (
+                Some("Advanced Micro Devices, Inc. [AMD/ATI]".to_string()),
+                Some("Radeon RX 6800/6800 XT / 6900 XT".to_string())
+            )
+        );
+        // Past a comment, and without a marketing name.
+        assert_eq!(lookup(0x10de, 0x2520).1.as_deref(), Some("GA106M"));
+        // Devices of the next vendor aren't mistaken for this one's.
+        assert_eq!(lookup(0x1002, 0x2504).1, None);
+        assert_eq!(lookup(0x1234, 0x1111), (None, None));
+        assert_eq!(lookup_pci_ids(None, 0x1002, 0x73bf), (None, None));
+    }
+
+    #[test]
+    fn amd_apus_are_sorted() {
+        for (first, last) in AMD_APUS {
+            assert!(first <= last, "{:04x}-{:04x}", first, last);
+        }
+        for pair in AMD_APUS.windows(2) {
+            assert!(
+                pair[0].1 < pair[1].0,
+                "{:04x?} overlaps {:04x?}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn amd_apus() {
+        // Phoenix, Rembrandt, Raven, Van Gogh (Steam Deck) and Kaveri.
+        for apu in [0x15bf, 0x1681, 0x15dd, 0x163f, 0x1313] {
+            assert!(is_amd_apu(apu), "{:04x}", apu);
+        }
+        // RX 6800 XT, RX 6400, RX 7600, RX 580 and Radeon VII.
+        for card in [0x73bf, 0x743f, 0x7480, 0x67df, 0x66af] {
+            assert!(!is_amd_apu(card), "{:04x}", card);
+        }
+    }
+
+    // A `cardN` entry in `drm_path` linking to `device`, with its PCI IDs if `ids` is given.
+    fn card(drm_path: &Path, card: &str, device: &Path, ids: Option<(&str, &str, &str)>) {
+        fs::create_dir_all(drm_path.join(card)).unwrap();
+        fs::create_dir_all(device).unwrap();
+        symlink(device, drm_path.join(card).join("device")).unwrap();
+        if let Some((vendor, device_id, slot)) = ids {
+            fs::write(device.join("vendor"), format!("{}\n", vendor)).unwrap();
+            fs::write(device.join("device"), format!("{}\n", device_id)).unwrap();
+            fs::write(
+                device.join("uevent"),
+                format!("DRIVER=amdgpu\nPCI_SLOT_NAME={}\n", slot),
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn gpus_from_drm() {
+        let root = TestDir::new("drm");
+        let drm = root.join("drm");
+        let devices = root.join("devices");
+        let apu = devices.join("0000:c4:00.0");
+        let card_device = devices.join("0000:03:00.0");
+        card(
+            &drm,
+            "card10",
+            &card_device,
+            Some(("0x1002", "0x73bf", "0000:03:00.0")),
+        );
+        card(
+            &drm,
+            "card1",
+            &apu,
+            Some(("0x1002", "0x15bf", "0000:c4:00.0")),
+        );
+        fs::write(apu.join("boot_vga"), "1\n").unwrap();
+        fs::write(card_device.join("mem_info_vram_total"), "17163091968\n").unwrap();
+        // A connector and a second card for the same device are left out.
+        fs::create_dir_all(drm.join("card1-eDP-1")).unwrap();
+        card(&drm, "card2", &apu, None);
+        card(&drm, "card3", &devices.join("gpu@fd4a0000"), None);
+        fs::write(devices.join("gpu@fd4a0000/uevent"), "DRIVER=panfrost\n").unwrap();
+
+        let gpus: Vec<(String, GpuKind, Option<u64>, bool)> = get_gpus_from(&drm)
+            .into_iter()
+            .map(|gpu| (gpu.vendor, gpu.kind, gpu.vram, gpu.primary))
+            .collect();
+        assert_eq!(
+            gpus,
+            [
+                ("AMD".to_string(), GpuKind::Integrated, None, true),
+                (String::new(), GpuKind::Integrated, None, false),
+                (
+                    "AMD".to_string(),
+                    GpuKind::Discrete,
+                    Some(17163091968),
+                    false
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,603 @@
+use crate::fns::get_winsize;
+use std::{env, fs};
+
+// Fallback cell size in pixels, for terminals that don't report theirs.
+const CELL_WIDTH: usize = 10;
+const CELL_HEIGHT: usize = 20;
+const SIXEL_LEVELS: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Iterm,
+    Sixel,
+    Blocks,
+}
+
+impl Protocol {
+    pub fn parse(protocol: &str) -> Option<Protocol> {
+        match protocol.to_ascii_lowercase().as_str() {
+            "auto" => Some(detect_protocol()),
+            "kitty" => Some(Protocol::Kitty),
+            "iterm" | "iterm2" => Some(Protocol::Iterm),
+            "sixel" => Some(Protocol::Sixel),
+            "blocks" | "unicode" => Some(Protocol::Blocks),
+            _ => None,
+        }
+    }
+}
+
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>,
+    png: Vec<u8>,
+}
+
+impl Image {
+    pub fn load(path: &str) -> Result<Image, String> {
+        let png = fs::read(path).map_err(|err| format!("Error reading image: {}", err))?;
+
+        let mut decoder = png::Decoder::new(png.as_slice());
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder
+            .read_info()
+            .map_err(|err| format!("Error decoding image: {}", err))?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let frame = reader
+            .next_frame(&mut buf)
+            .map_err(|err| format!("Error decoding image: {}", err))?;
+        let buf = &buf[..frame.buffer_size()];
+
+        let pixels = match frame.color_type {
+            png::ColorType::Rgba => buf
+                .chunks_exact(4)
+                .map(|p| [p[0], p[1], p[2], p[3]])
+                .collect(),
+            png::ColorType::Rgb => buf
+                .chunks_exact(3)
+                .map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buf
+                .chunks_exact(2)
+                .map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buf.iter().map(|&p| [p, p, p, 255]).collect(),
+            png::ColorType::Indexed => return Err("Unsupported image color type".to_string()),
+        };
+
+        Ok(Image {
+            width: frame.width as usize,
+            height: frame.height as usize,
+            pixels,
+            png,
+        })
+    }
+
+    // Box filter, averaging every source pixel that falls within a target pixel.
+    pub fn resize(&self, width: usize, height: usize) -> Image {
+        let width = width.max(1);
+        let height = height.max(1);
+        let mut pixels = Vec::with_capacity(width * height);
+
+        // Source range covered by target pixel `i`, at least one pixel wide.
+        let span = |i: usize, target: usize, source: usize| {
+            let start = (i * source / target).min(source - 1);
+            start..((i + 1) * source / target).clamp(start + 1, source)
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0usize; 4];
+                let mut count = 0;
+                for sy in span(y, height, self.height) {
+                    for sx in span(x, width, self.width) {
+                        let pixel = self.pixels[sy * self.width + sx];
+                        for (channel, value) in sum.iter_mut().zip(pixel) {
+                            *channel += value as usize;
+                        }
+                        count += 1;
+                    }
+                }
+                let count = count.max(1);
+                pixels.push(sum.map(|channel| (channel / count) as u8));
+            }
+        }
+
+        Image {
+            width,
+            height,
+            pixels,
+            png: vec![],
+        }
+    }
+
+    /// Height in terminal rows when drawn `columns` cells wide.
+    pub fn rows(&self, columns: usize) -> usize {
+        let (cell_width, cell_height) = cell_size();
+        (self.height * columns * cell_width).div_ceil(self.width.max(1) * cell_height)
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixels[y * self.width + x]
+    }
+}
+
+pub fn detect_protocol() -> Protocol {
+    let var = |name| env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    let term_program = var("TERM_PROGRAM");
+
+    // Multiplexers don't pass graphics through without extra configuration.
+    if env::var("TMUX").is_ok() || term.starts_with("screen") {
+        return Protocol::Blocks;
+    }
+
+    if env::var("KITTY_WINDOW_ID").is_ok() || term == "xterm-kitty" || term_program == "ghostty" {
+        Protocol::Kitty
+    } else if term_program == "iTerm.app"
+        || term_program == "WezTerm"
+        || var("LC_TERMINAL") == "iTerm2"
+    {
+        Protocol::Iterm
+    } else if term.starts_with("foot")
+        || term.starts_with("mlterm")
+        || term.contains("sixel")
+        || term_program == "contour"
+        || env::var("WT_SESSION").is_ok()
+    {
+        Protocol::Sixel
+    } else {
+        Protocol::Blocks
+    }
+}
+
+fn cell_size() -> (usize, usize) {
+    match get_winsize() {
+        Some(size) if size.ws_xpixel > 0 && size.ws_ypixel > 0 => (
+            size.ws_xpixel as usize / size.ws_col as usize,
+            size.ws_ypixel as usize / size.ws_row as usize,
+        ),
+        _ => (CELL_WIDTH, CELL_HEIGHT),
+    }
+}
+
+pub fn render(image: &Image, protocol: Protocol, columns: usize) -> String {
+    match protocol {
+        Protocol::Kitty => encode_kitty(&image.png, columns),
+        Protocol::Iterm => encode_iterm(&image.png, columns),
+        Protocol::Sixel => {
+            let (cell_width, _) = cell_size();
+            let width = columns * cell_width;
+            let height = image.height * width / image.width.max(1);
+            encode_sixel(&image.resize(width, height))
+        }
+        Protocol::Blocks => {
+            // Every cell holds two pixels stacked on top of each other.
+            encode_blocks(&image.resize(columns, image.rows(columns) * 2))
+        }
+    }
+}
+
+pub fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let triple = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> (18 - i * 6) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+// https://sw.kovidgoyal.net/kitty/graphics-protocol/, sending the PNG as-is in 4096 byte chunks.
+pub fn encode_kitty(png: &[u8], columns: usize) -> String {
+    let data = base64(png);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    let mut encoded = String::new();
+
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let more = (idx + 1 < chunks.len()) as u8;
+        let chunk = String::from_utf8_lossy(chunk);
+        if idx == 0 {
+            encoded.push_str(&format!(
+                "\x1b_Ga=T,f=100,c={},m={};{}\x1b\\",
+                columns, more, chunk
+            ));
+        } else {
+            encoded.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+
+    encoded
+}
+
+// https://iterm2.com/documentation-images.html
+pub fn encode_iterm(png: &[u8], columns: usize) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        columns,
+        base64(png)
+    )
+}
+
+// Quantizes to a 6x6x6 color cube, pixels that are mostly transparent are left empty.
+pub fn encode_sixel(image: &Image) -> String {
+    let level = |value: u8| value as usize * (SIXEL_LEVELS - 1) / 255;
+    let register = |[r, g, b, a]: [u8; 4]| {
+        (a >= 128).then(|| (level(r) * SIXEL_LEVELS + level(g)) * SIXEL_LEVELS + level(b))
+    };
+
+    let mut encoded = format!("\x1bP0;1;0q\"1;1;{};{}", image.width, image.height);
+    for idx in 0..SIXEL_LEVELS.pow(3) {
+        let percent = |value: usize| value * 100 / (SIXEL_LEVELS - 1);
+        encoded.push_str(&format!(
+            "#{};2;{};{};{}",
+            idx,
+            percent(idx / (SIXEL_LEVELS * SIXEL_LEVELS)),
+            percent(idx / SIXEL_LEVELS % SIXEL_LEVELS),
+            percent(idx % SIXEL_LEVELS)
+        ));
+    }
+
+    for band in (0..image.height).step_by(6) {
+        let mut colors: Vec<usize> = (band..(band + 6).min(image.height))
+            .flat_map(|y| (0..image.width).filter_map(move |x| register(image.pixel(x, y))))
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (idx, color) in colors.iter().enumerate() {
+            if idx > 0 {
+                encoded.push('$');
+            }
+            encoded.push_str(&format!("#{}", color));
+
+            let sixels = (0..image.width).map(|x| {
+                let bits = (0..6)
+                    .filter(|row| {
+                        band + row < image.height
+                            && register(image.pixel(x, band + row)) == Some(*color)
+                    })
+                    .fold(0, |bits, row| bits | 1 << row);
+                (b'?' + bits) as char
+            });
+            push_runs(&mut encoded, sixels);
+        }
+        encoded.push('-');
+    }
+
+    encoded.push_str("\x1b\\");
+    encoded
+}
+
+fn push_runs(encoded: &mut String, sixels: impl Iterator<Item = char>) {
+    let mut flush = |sixel: char, count: usize| match count {
+        0 => {}
+        1..=3 => encoded.extend(std::iter::repeat_n(sixel, count)),
+        _ => encoded.push_str(&format!("!{}{}", count, sixel)),
+    };
+
+    let mut run = ('?', 0);
+    for sixel in sixels {
+        if sixel == run.0 {
+            run.1 += 1;
+        } else {
+            flush(run.0, run.1);
+            run = (sixel, 1);
+        }
+    }
+    flush(run.0, run.1);
+}
+
+// Half blocks with the upper pixel as foreground and the lower one as background.
+pub fn encode_blocks(image: &Image) -> String {
+    let opaque = |[r, g, b, a]: [u8; 4]| (a >= 128).then_some((r, g, b));
+    let mut lines = vec![];
+
+    for y in (0..image.height).step_by(2) {
+        let mut line = String::new();
+        for x in 0..image.width {
+            let top = opaque(image.pixel(x, y));
+            let bottom = (y + 1 < image.height)
+                .then(|| opaque(image.pixel(x, y + 1)))
+                .flatten();
+
+            match (top, bottom) {
+                (Some((r, g, b)), Some((br, bg, bb))) => line.push_str(&format!(
+                    "\x1b[38;2;{};{};{};48;2;{};{};{}m▀",
+                    r, g, b, br, bg, bb
+                )),
+                (Some((r, g, b)), None) => line.push_str(&format!("\x1b[38;2;{};{};{}m▀", r, g, b)),
+                (None, Some((r, g, b))) => line.push_str(&format!("\x1b[38;2;{};{};{}m▄", r, g, b)),
+                (None, None) => line.push(' '),
+            }
+            line.push_str("\x1b[0m");
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Charset {
+    Ascii,
+    Blocks,
+}
+
+impl Charset {
+    pub fn parse(charset: &str) -> Option<Charset> {
+        match charset.to_ascii_lowercase().as_str() {
+            "ascii" => Some(Charset::Ascii),
+            "blocks" | "unicode" => Some(Charset::Blocks),
+            _ => None,
+        }
+    }
+}
+
+// Darkest to brightest, the first one is only used for transparent pixels.
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+const KMEANS_ITERATIONS: usize = 10;
+
+// A character and the color it's drawn in.
+type Cell = (char, [u8; 3]);
+
+/// Converts an image to the source of a logo file, `columns` cells wide, with its colors
+/// reduced to `color_count` `${cN}` slots.
+pub fn to_logo_source(
+    image: &Image,
+    name: &str,
+    matches: &str,
+    columns: usize,
+    charset: Charset,
+    color_count: usize,
+) -> String {
+    let rows = image.rows(columns).max(1);
+    let cells: Vec<Vec<Option<Cell>>> = match charset {
+        Charset::Ascii => {
+            let image = image.resize(columns, rows);
+            (0..image.height)
+                .map(|y| {
+                    (0..image.width)
+                        .map(|x| {
+                            let [r, g, b, a] = image.pixel(x, y);
+                            (a >= 128).then(|| {
+                                let luma =
+                                    (r as usize * 299 + g as usize * 587 + b as usize * 114) / 1000;
+                                let idx = 1 + luma * (ASCII_RAMP.len() - 2) / 255;
+                                (ASCII_RAMP[idx] as char, [r, g, b])
+                            })
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+        Charset::Blocks => {
+            let image = image.resize(columns, rows * 2);
+            (0..rows)
+                .map(|row| {
+                    (0..image.width)
+                        .map(|x| {
+                            let half = |y: usize| {
+                                (y < image.height)
+                                    .then(|| image.pixel(x, y))
+                                    .filter(|[_, _, _, a]| *a >= 128)
+                            };
+                            match (half(row * 2), half(row * 2 + 1)) {
+                                (Some(top), Some(bottom)) => Some((
+                                    '█',
+                                    [0, 1, 2]
+                                        .map(|c| ((top[c] as u16 + bottom[c] as u16) / 2) as u8),
+                                )),
+                                (Some([r, g, b, _]), None) => Some(('▀', [r, g, b])),
+                                (None, Some([r, g, b, _])) => Some(('▄', [r, g, b])),
+                                (None, None) => None,
+                            }
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    };
+
+    let colors: Vec<[u8; 3]> = cells
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|(_, rgb)| *rgb)
+        .collect();
+    let palette = quantize(&colors, color_count);
+
+    let mut art = String::new();
+    let mut current_slot = 0;
+    for line in &cells {
+        for cell in line {
+            match cell {
+                Some((character, rgb)) => {
+                    let slot = nearest(&palette, *rgb) + 1;
+                    if slot != current_slot {
+                        art.push_str(&format!("${{c{}}}", slot));
+                        current_slot = slot;
+                    }
+                    art.push(*character);
+                }
+                None => art.push(' '),
+            }
+        }
+        let trimmed = art.trim_end_matches(' ').len();
+        art.truncate(trimmed);
+        art.push('\n');
+    }
+
+    let hex_colors: Vec<String> = palette
+        .iter()
+        .map(|[r, g, b]| format!("#{:02X}{:02X}{:02X}", r, g, b))
+        .collect();
+
+    format!(
+        "name: {}\nmatch: {}\ncolors: {}\n---\n{}",
+        name,
+        matches,
+        hex_colors.join(", "),
+        art
+    )
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(&a, b)| (a as i32 - b as i32).pow(2) as u32)
+        .sum()
+}
+
+fn nearest(palette: &[[u8; 3]], rgb: [u8; 3]) -> usize {
+    (0..palette.len())
+        .min_by_key(|&idx| distance(palette[idx], rgb))
+        .unwrap_or(0)
+}
+
+// K-means, seeded with colors spread evenly over the brightness range.
+fn quantize(colors: &[[u8; 3]], count: usize) -> Vec<[u8; 3]> {
+    let mut unique = colors.to_vec();
+    unique.sort_unstable_by_key(|[r, g, b]| {
+        (
+            *r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114,
+            *r,
+            *g,
+            *b,
+        )
+    });
+    unique.dedup();
+    if unique.is_empty() {
+        return vec![[255, 255, 255]];
+    }
+
+    let count = count.clamp(1, unique.len());
+    let mut palette: Vec<[u8; 3]> = (0..count)
+        .map(|idx| unique[idx * (unique.len() - 1) / (count - 1).max(1)])
+        .collect();
+
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut sums = vec![([0u64; 3], 0u64); palette.len()];
+        for rgb in colors {
+            let (sum, total) = &mut sums[nearest(&palette, *rgb)];
+            for (channel, value) in sum.iter_mut().zip(rgb) {
+                *channel += *value as u64;
+            }
+            *total += 1;
+        }
+
+        palette = sums
+            .iter()
+            .filter(|(_, total)| *total > 0)
+            .map(|(sum, total)| sum.map(|channel| (channel / total) as u8))
+            .collect();
+    }
+
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+    fn image(width: usize, pixels: Vec<[u8; 4]>) -> Image {
+        Image {
+            width,
+            height: pixels.len() / width,
+            pixels,
+            png: vec![],
+        }
+    }
+
+    #[test]
+    fn base64_matches_rfc_4648() {
+        for (data, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(data.as_bytes()), encoded);
+        }
+    }
+
+    #[test]
+    fn kitty_sends_small_images_at_once() {
+        assert_eq!(
+            encode_kitty(b"\x89PNG", 20),
+            "\x1b_Ga=T,f=100,c=20,m=0;iVBORw==\x1b\\"
+        );
+    }
+
+    #[test]
+    fn kitty_splits_data_into_4096_byte_chunks() {
+        // 3072 bytes are exactly 4096 base64 characters.
+        let single = encode_kitty(&[0; 3072], 20);
+        assert_eq!(single.matches("\x1b_G").count(), 1);
+        assert!(single.starts_with("\x1b_Ga=T,f=100,c=20,m=0;AAAA"));
+
+        let split = encode_kitty(&[0; 3073], 20);
+        let first = format!("\x1b_Ga=T,f=100,c=20,m=1;{}\x1b\\", "A".repeat(4096));
+        assert_eq!(split, format!("{}\x1b_Gm=0;AA==\x1b\\", first));
+    }
+
+    #[test]
+    fn iterm_sends_the_png_inline() {
+        assert_eq!(
+            encode_iterm(b"\x89PNG", 20),
+            "\x1b]1337;File=inline=1;size=4;width=20;preserveAspectRatio=1:iVBORw==\x07"
+        );
+    }
+
+    #[test]
+    fn sixel_uses_a_color_cube_palette() {
+        let encoded = encode_sixel(&image(2, vec![RED, BLUE, CLEAR, RED]));
+        assert!(encoded.starts_with("\x1bP0;1;0q\"1;1;2;2#0;2;0;0;0#1;2;0;0;20#"));
+        assert!(encoded.contains("#5;2;0;0;100#"));
+        assert!(encoded.contains("#180;2;100;0;0#"));
+        // Blue only in the top right pixel, red in the top left and bottom right.
+        assert!(encoded.ends_with("#215;2;100;100;100#5?@$#180@A-\x1b\\"));
+    }
+
+    #[test]
+    fn sixel_runs_are_length_encoded() {
+        let encoded = encode_sixel(&image(10, vec![RED; 10]));
+        assert!(encoded.ends_with("#180!10@-\x1b\\"));
+
+        let mut runs = String::new();
+        push_runs(&mut runs, "??@@@@@A".chars());
+        assert_eq!(runs, "??!5@A");
+    }
+
+    #[test]
+    fn blocks_stack_two_pixels_per_cell() {
+        assert_eq!(
+            encode_blocks(&image(2, vec![RED, CLEAR, BLUE, RED, BLUE, CLEAR])),
+            "\x1b[38;2;255;0;0;48;2;0;0;255m▀\x1b[0m\x1b[38;2;255;0;0m▄\x1b[0m\n\
+             \x1b[38;2;0;0;255m▀\x1b[0m \x1b[0m"
+        );
+    }
+}
@@ -1,9 +1,183 @@
 use crate::fns::home_dir;
+use crate::size::SizeFormat;
+use crate::temps::TempUnit;
+use colored::Colorize;
 use serde::Deserialize;
 use std::error::Error;
 use std::fs;
 use std::io;
 
+#[derive(Debug, Default, Deserialize)]
+pub struct Options {
+    #[serde(default)]
+    pub logo: LogoOptions,
+    #[serde(default)]
+    pub temperature: TemperatureOptions,
+    #[serde(default)]
+    pub cpu: CpuOptions,
+    #[serde(default)]
+    pub size: SizeOptions,
+    #[serde(default)]
+    pub disk: DiskOptions,
+    #[serde(default)]
+    pub shell: ShellOptions,
+    #[serde(default)]
+    pub kernel: KernelOptions,
+    #[serde(default)]
+    pub network: NetworkOptions,
+    #[serde(default)]
+    pub redact: RedactOptions,
+    #[serde(default)]
+    pub model: ModelOptions,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LogoOptions {
+    pub image: Option<String>,
+    pub protocol: String,
+    pub chars: String,
+    pub width: usize,
+}
+
+impl Default for LogoOptions {
+    fn default() -> Self {
+        LogoOptions {
+            image: None,
+            protocol: "auto".to_string(),
+            chars: "ascii".to_string(),
+            width: 30,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct TemperatureOptions {
+    pub unit: String,
+    pub cpu: bool,
+    pub gpu: bool,
+}
+
+impl Default for TemperatureOptions {
+    fn default() -> Self {
+        TemperatureOptions {
+            unit: "celsius".to_string(),
+            cpu: true,
+            gpu: true,
+        }
+    }
+}
+
+impl TemperatureOptions {
+    pub fn unit(&self) -> TempUnit {
+        TempUnit::parse(&self.unit).unwrap_or_else(|| {
+            println!(
+                "[{}] Unknown temperature unit \"{}\", using celsius.",
+                "WARNING".yellow(),
+                self.unit
+            );
+            TempUnit::Celsius
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct CpuOptions {
+    pub template: String,
+}
+
+impl Default for CpuOptions {
+    fn default() -> Self {
+        CpuOptions {
+            template: "{model}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SizeOptions {
+    pub units: String,
+    pub precision: usize,
+    pub unit: String,
+}
+
+impl Default for SizeOptions {
+    fn default() -> Self {
+        SizeOptions {
+            units: "binary".to_string(),
+            precision: 2,
+            unit: "auto".to_string(),
+        }
+    }
+}
+
+impl SizeOptions {
+    pub fn format(&self) -> SizeFormat {
+        SizeFormat::parse(&self.units, self.precision, &self.unit).unwrap_or_else(|| {
+            println!(
+                "[{}] Unknown size units \"{}\" or unit \"{}\", using binary units.",
+                "WARNING".yellow(),
+                self.units,
+                self.unit
+            );
+            SizeFormat {
+                decimal: false,
+                precision: self.precision,
+                exponent: None,
+            }
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DiskOptions {
+    pub mounts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct KernelOptions {
+    pub template: String,
+}
+
+impl Default for KernelOptions {
+    fn default() -> Self {
+        KernelOptions {
+            template: "{release}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ShellOptions {
+    pub version: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct NetworkOptions {
+    pub interfaces: Vec<String>,
+    pub mask: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RedactOptions {
+    pub enabled: bool,
+    pub modules: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ModelOptions {
+    pub bios: bool,
+}
+
 #[derive(Debug, Deserialize)]
 struct Info {
     info1: Vec<String>,
@@ -61,3 +235,18 @@ pub fn get_info(
 
     returned_vec.unwrap_or_else(|_err| get_default_info_lists(section))
 }
+
+// Module options live next to the info lists in the info config, and fall back to their
+// defaults when missing or invalid.
+pub fn get_options(use_custom_config: bool, custom_config_file: Option<String>) -> Options {
+    if !use_custom_config {
+        return Options::default();
+    }
+
+    let path =
+        custom_config_file.unwrap_or_else(|| format!("{}/.config/rsftch/info.json", home_dir()));
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
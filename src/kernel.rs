@@ -0,0 +1,224 @@
+use crate::fns::fill_template;
+use std::{ffi::CStr, fs, mem};
+
+// Suffixes of kernel releases built with other patches or configuration, e.g.
+// "6.9.3-zen1-1-zen" or "6.8.0-35-generic".
+const FLAVOURS: [&str; 18] = [
+    "zen",
+    "lts",
+    "rt",
+    "hardened",
+    "xanmod",
+    "liquorix",
+    "cachyos",
+    "tkg",
+    "generic",
+    "lowlatency",
+    "raspi",
+    "rpi",
+    "pve",
+    "surface",
+    "asahi",
+    "aws",
+    "azure",
+    "gcp",
+];
+
+// Bit, letter and meaning of each flag in /proc/sys/kernel/tainted, see
+// Documentation/admin-guide/tainted-kernels.rst.
+const TAINT_FLAGS: [(u32, char, &str); 20] = [
+    (0, 'P', "proprietary module"),
+    (1, 'F', "module force loaded"),
+    (2, 'S', "out of spec system"),
+    (3, 'R', "module force unloaded"),
+    (4, 'M', "machine check"),
+    (5, 'B', "bad page"),
+    (6, 'U', "tainted by user"),
+    (7, 'D', "kernel died recently"),
+    (8, 'A', "ACPI table overridden"),
+    (9, 'W', "kernel warning"),
+    (10, 'C', "staging driver"),
+    (11, 'I', "firmware workaround"),
+    (12, 'O', "out-of-tree module"),
+    (13, 'E', "unsigned module"),
+    (14, 'L', "soft lockup"),
+    (15, 'K', "live patched"),
+    (16, 'X', "auxiliary taint"),
+    (17, 'T', "struct randomization"),
+    (18, 'N', "in-kernel test"),
+    (19, 'J', "fwctl debug"),
+];
+
+// uname's release, machine and version.
+fn uname() -> Option<(String, String, String)> {
+    let mut uts: libc::utsname = unsafe { mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return None;
+    }
+
+    let field = |field: &[libc::c_char]| {
+        unsafe { CStr::from_ptr(field.as_ptr()) }
+            .to_string_lossy()
+            .to_string()
+    };
+    Some((
+        field(&uts.release),
+        field(&uts.machine),
+        field(&uts.version),
+    ))
+}
+
+fn get_flavour(release: &str) -> Option<String> {
+    release
+        .split(['-', '+', '.', '_'])
+        .skip(1)
+        .find_map(|part| {
+            let part = part.to_lowercase();
+            FLAVOURS
+                .iter()
+                .find(|flavour| {
+                    part.strip_prefix(*flavour)
+                        .is_some_and(|rest| rest.bytes().all(|byte| byte.is_ascii_digit()))
+                })
+                .map(|flavour| flavour.to_string())
+        })
+}
+
+/// The build date at the end of the version, either after the weekday like in
+/// "#1 SMP PREEMPT_DYNAMIC Thu, 30 May 2024 20:48:40 +0000", or in brackets like in
+/// "#1 SMP PREEMPT_DYNAMIC Debian 6.1.90-1 (2024-05-03)".
+fn get_build_date(version: &str) -> Option<String> {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+    if let Some(start) = version
+        .split_whitespace()
+        .find(|word| WEEKDAYS.contains(&word.trim_end_matches(',')))
+        .and_then(|weekday| version.find(weekday))
+    {
+        return Some(version[start..].trim().to_string());
+    }
+
+    let date = version.trim().strip_suffix(')')?;
+    let date = &date[date.rfind('(')? + 1..];
+    date.starts_with(|c: char| c.is_ascii_digit())
+        .then(|| date.to_string())
+}
+
+fn get_preemption(version: &str) -> Option<&'static str> {
+    version.split_whitespace().find_map(|word| match word {
+        "PREEMPT_DYNAMIC" => Some("dynamic preemption"),
+        "PREEMPT_RT" => Some("real-time preemption"),
+        "PREEMPT" => Some("full preemption"),
+        _ => None,
+    })
+}
+
+/// The flags set in /proc/sys/kernel/tainted, e.g. "tainted PO: proprietary module,
+/// out-of-tree module".
+fn format_taint(tainted: u64) -> String {
+    if tainted == 0 {
+        return "not tainted".to_string();
+    }
+
+    let flags: Vec<&(u32, char, &str)> = TAINT_FLAGS
+        .iter()
+        .filter(|(bit, _, _)| tainted & (1 << bit) != 0)
+        .collect();
+    // Flags newer than this list.
+    if flags.is_empty() {
+        return format!("tainted (0x{:x})", tainted);
+    }
+    format!(
+        "tainted {}: {}",
+        flags
+            .iter()
+            .map(|(_, letter, _)| letter.to_string())
+            .collect::<String>(),
+        flags
+            .iter()
+            .map(|(_, _, meaning)| *meaning)
+            .collect::<Vec<&str>>()
+            .join(", ")
+    )
+}
+
+fn get_taint() -> Option<String> {
+    let tainted = fs::read_to_string("/proc/sys/kernel/tainted").ok()?;
+    Some(format_taint(tainted.trim().parse().ok()?))
+}
+
+/// The `kernel` module, from a template like "{release} {arch} [({flavour}, {preempt})]".
+pub fn get_kernel(template: &str) -> String {
+    let Some((release, arch, uname_version)) = uname() else {
+        return String::new();
+    };
+
+    // "Linux version <release> (<builder>) (<compiler>) #1 SMP ...", uname's version
+    // being the part from "#".
+    let version = fs::read_to_string("/proc/version")
+        .ok()
+        .and_then(|version| Some(version[version.find('#')?..].trim().to_string()))
+        .unwrap_or(uname_version);
+
+    fill_template(
+        template,
+        &[
+            ("{release}", release.clone()),
+            ("{arch}", arch),
+            ("{flavour}", get_flavour(&release).unwrap_or_default()),
+            ("{build_date}", get_build_date(&version).unwrap_or_default()),
+            (
+                "{preempt}",
+                get_preemption(&version).unwrap_or_default().to_string(),
+            ),
+            ("{taint}", get_taint().unwrap_or_default()),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flavours() {
+        assert_eq!(get_flavour("6.9.3-zen1-1-zen").as_deref(), Some("zen"));
+        assert_eq!(get_flavour("6.8.0-35-generic").as_deref(), Some("generic"));
+        assert_eq!(get_flavour("6.6.32-1-lts").as_deref(), Some("lts"));
+        assert_eq!(get_flavour("6.8.0-1009-raspi").as_deref(), Some("raspi"));
+        assert_eq!(get_flavour("6.6.31+rpt-rpi-v8").as_deref(), Some("rpi"));
+        // "arch1" isn't a flavour, neither is the Debian architecture.
+        assert_eq!(get_flavour("6.9.3-arch1-1"), None);
+        assert_eq!(get_flavour("6.1.0-21-amd64"), None);
+        assert_eq!(get_flavour("zen"), None);
+    }
+
+    #[test]
+    fn build_dates() {
+        assert_eq!(
+            get_build_date("#1 SMP PREEMPT_DYNAMIC Thu, 30 May 2024 20:48:40 +0000").as_deref(),
+            Some("Thu, 30 May 2024 20:48:40 +0000")
+        );
+        assert_eq!(
+            get_build_date("#35-Ubuntu SMP PREEMPT_DYNAMIC Mon May 20 15:51:52 UTC 2024")
+                .as_deref(),
+            Some("Mon May 20 15:51:52 UTC 2024")
+        );
+        assert_eq!(
+            get_build_date("#1 SMP PREEMPT_DYNAMIC Debian 6.1.90-1 (2024-05-03)").as_deref(),
+            Some("2024-05-03")
+        );
+        assert_eq!(get_build_date("#1 SMP (custom)"), None);
+        assert_eq!(get_build_date("#1 SMP"), None);
+    }
+
+    #[test]
+    fn taint() {
+        assert_eq!(format_taint(0), "not tainted");
+        assert_eq!(
+            format_taint(1 | 1 << 12),
+            "tainted PO: proprietary module, out-of-tree module"
+        );
+        assert_eq!(format_taint(1 << 30), "tainted (0x40000000)");
+    }
+}
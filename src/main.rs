@@ -1,22 +1,63 @@
 use colored::Color;
 use colored::Colorize;
 use std::env;
+use std::fs;
 use std::mem;
+use std::path::Path;
 
 mod ascii;
+mod battery;
 mod color_config;
+mod cpu;
+mod desktop;
+mod disk;
+mod display;
 mod fns;
+mod gpu;
+mod image;
 mod info_config;
+mod kernel;
+mod memory;
+mod model;
+mod network;
+mod process;
+mod redact;
+mod shell;
+mod size;
+mod storage;
+mod temps;
+mod terminal;
+mod version;
 
 use crate::ascii::*;
+use crate::battery::get_battery;
 use crate::color_config::*;
+use crate::desktop::get_desktop;
+use crate::disk::get_disk_usage;
+use crate::display::get_res;
 use crate::fns::*;
+use crate::gpu::get_gpus;
+use crate::image::{render, to_logo_source, Charset, Image, Protocol};
 use crate::info_config::*;
+use crate::kernel::get_kernel;
+use crate::memory::{get_mem, get_swap, get_zram};
+use crate::model::get_model;
+use crate::network::get_network;
+use crate::redact::Redactor;
+use crate::shell::get_shell;
+use crate::storage::get_storage;
+use crate::temps::get_temps;
+use crate::terminal::get_terminal;
 
 const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
 
 fn main() {
     let mut args: Vec<String> = env::args().collect();
+
+    if args.len() > 2 && args[1] == "logo" && args[2] == "convert" {
+        return convert_logo(&mut args[3..]);
+    }
+
     let mut overriden_ascii: Option<String> = None;
     let mut info_custom_config: Option<String> = None;
     let mut color_custom_config: Option<String> = None;
@@ -24,6 +65,8 @@ fn main() {
     let mut use_info_custom_config = true;
     let mut use_color_custom_config = true;
     let mut margin: i8 = 1;
+    let mut logo_size = LogoSize::Auto;
+    let mut redact = false;
 
     for count in 0..args.len() {
         match args[count].to_lowercase().as_str() {
@@ -33,6 +76,7 @@ fn main() {
             }
             "--ignore-color-config" => use_color_custom_config = false,
             "--ignore-info-config" => use_info_custom_config = false,
+            "--redact" => redact = true,
             "--ignore-config" => {
                 use_color_custom_config = false;
                 use_info_custom_config = false;
@@ -53,6 +97,18 @@ fn main() {
                     return help();
                 }
             }
+            "-l" | "--logo-size" => {
+                match args.get(count + 1).and_then(|size| LogoSize::parse(size)) {
+                    Some(size) => logo_size = size,
+                    None => {
+                        println!(
+                            "[{}] Missing or invalid argument for logo size.\n",
+                            "ERROR".red()
+                        );
+                        return help();
+                    }
+                }
+            }
             "-o" | "--override" => {
                 if count + 1 < args.len() && !args[count + 1].starts_with("-") {
                     overriden_ascii = Some(mem::take(&mut args[count + 1]));
@@ -92,16 +148,128 @@ fn main() {
 
     println!(
         "{}",
-        info(
+        info(Settings {
             overriden_ascii,
             margin,
-            use_info_custom_config,
-            !use_color_custom_config,
-            info_custom_config,
-            color_custom_config,
+            use_custom_info_config: use_info_custom_config,
+            use_custom_color_config: !use_color_custom_config,
+            custom_info_config_file: info_custom_config,
+            custom_color_config_file: color_custom_config,
             get_only_info,
-        )
+            logo_size,
+            redact,
+        })
+    );
+}
+
+fn convert_logo(args: &mut [String]) {
+    let mut image_path: Option<String> = None;
+    let mut output: Option<String> = None;
+    let mut name: Option<String> = None;
+    let mut matches: Option<String> = None;
+    let mut width: usize = 30;
+    let mut colors: usize = MAX_COLOR_SLOTS;
+    let mut charset = Charset::Ascii;
+
+    let mut args = args.iter_mut().peekable();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            if image_path.is_some() {
+                return println!(
+                    "[{}] Unexpected argument \"{}\", only one image can be converted.",
+                    "ERROR".red(),
+                    arg
+                );
+            }
+            image_path = Some(mem::take(arg));
+            continue;
+        }
+
+        let value = args.next_if(|value| !value.starts_with('-')).map(mem::take);
+        match arg.to_lowercase().as_str() {
+            "-w" | "--width" => match value.and_then(|width| width.parse().ok()) {
+                Some(columns) => width = columns,
+                None => {
+                    return println!("[{}] Missing or invalid argument for width.", "ERROR".red())
+                }
+            },
+            "--colors" => match value.and_then(|count| count.parse().ok()) {
+                Some(count) => colors = count,
+                None => {
+                    return println!(
+                        "[{}] Missing or invalid argument for colors.",
+                        "ERROR".red()
+                    )
+                }
+            },
+            "--chars" => match value.as_deref().and_then(Charset::parse) {
+                Some(chars) => charset = chars,
+                None => {
+                    return println!("[{}] Missing or invalid argument for chars.", "ERROR".red())
+                }
+            },
+            "--name" | "--output" | "--match" if value.is_none() => {
+                return println!("[{}] Missing argument for {}.", "ERROR".red(), arg)
+            }
+            "--name" => name = value,
+            "--output" => output = value,
+            "--match" => matches = value,
+            _ => {
+                println!("[{}] Unknown option \"{}\".\n", "ERROR".red(), arg);
+                return help();
+            }
+        }
+    }
+
+    let Some(image_path) = image_path else {
+        println!("[{}] Missing image to convert.\n", "ERROR".red());
+        return help();
+    };
+
+    let image = match Image::load(&image_path) {
+        Ok(image) => image,
+        Err(err) => return println!("[{}] {}", "ERROR".red(), err),
+    };
+
+    let name = name.unwrap_or_else(|| {
+        Path::new(&image_path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "logo".to_string())
+    });
+    let id = logo_id(&name);
+    let output =
+        output.unwrap_or_else(|| format!("{}/.config/rsftch/logos/{}.txt", home_dir(), id));
+
+    let source = to_logo_source(
+        &image,
+        &name,
+        &matches.unwrap_or(id),
+        width,
+        charset,
+        colors.clamp(1, MAX_COLOR_SLOTS),
     );
+    let written = Path::new(&output)
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&output, source));
+
+    match written {
+        Ok(_) => println!("Wrote logo to {}", output),
+        Err(err) => println!("[{}] Couldn't write {}: {}", "ERROR".red(), output, err),
+    }
+}
+
+struct Settings {
+    overriden_ascii: Option<String>,
+    margin: i8,
+    use_custom_info_config: bool,
+    use_custom_color_config: bool,
+    custom_info_config_file: Option<String>,
+    custom_color_config_file: Option<String>,
+    get_only_info: Option<String>,
+    logo_size: LogoSize,
+    redact: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -112,26 +280,28 @@ struct InfoItem {
     value: String,
 }
 
-fn print_ascii(
-    ascii_art: String,
-    color: Color,
-    overriden_colors: bool,
-    custom_color_config_file: Option<String>,
-) -> String {
-    if !overriden_colors {
-        ascii_art.color(color).bold().to_string()
-    } else {
-        print_ascii(
-            ascii_art,
-            get_color_config(
-                "color0".to_string(),
-                !overriden_colors,
-                custom_color_config_file.clone(),
-            ),
-            false,
-            custom_color_config_file,
-        )
-    }
+fn print_ascii(logo: &Logo, custom_colors: Option<&JsonColors>) -> String {
+    let custom_color =
+        |section: &str| custom_colors.and_then(|colors| colors.get_color_by_section(section));
+    let default_color = get_color_config("color0", custom_colors);
+
+    // Each slot takes `logoN` from the color config, then `color0` like before logos had
+    // several colors, then the logo's own color, falling back to its first color and the
+    // default.
+    let slot_colors: Vec<Color> = (1..=MAX_COLOR_SLOTS)
+        .map(|slot| {
+            custom_color(&format!("logo{}", slot))
+                .or_else(|| custom_color("color0"))
+                .or_else(|| logo.colors.get(slot - 1).copied())
+                .or_else(|| logo.colors.first().copied())
+                .unwrap_or(default_color)
+        })
+        .collect();
+
+    logo.segments()
+        .iter()
+        .map(|(slot, text)| text.color(slot_colors[slot - 1]).bold().to_string())
+        .collect()
 }
 
 fn print_data(infos: &InfoItem, color: Color, connector: &'static str) -> String {
@@ -150,15 +320,33 @@ fn print_data(infos: &InfoItem, color: Color, connector: &'static str) -> String
     .to_string()
 }
 
-fn info(
-    overriden_ascii: Option<String>,
-    margin: i8,
-    use_custom_info_config: bool,
-    use_custom_color_config: bool,
-    custom_info_config_file: Option<String>,
-    custom_color_config_file: Option<String>,
-    get_only_info: Option<String>,
-) -> String {
+fn info(settings: Settings) -> String {
+    let Settings {
+        overriden_ascii,
+        margin,
+        use_custom_info_config,
+        use_custom_color_config,
+        custom_info_config_file,
+        custom_color_config_file,
+        get_only_info,
+        logo_size,
+        redact,
+    } = settings;
+    let options = get_options(use_custom_info_config, custom_info_config_file.clone());
+    let temp_unit = options.temperature.unit();
+    let size = options.size.format();
+    let lists: Vec<Vec<String>> = ["info1", "info2", "info3"]
+        .iter()
+        .map(|set| get_info(set, use_custom_info_config, custom_info_config_file.clone()))
+        .collect();
+    let shown = |names: &[&str]| {
+        let is_named = |name: &String| names.contains(&name.to_lowercase().as_str());
+        match &get_only_info {
+            Some(only_info) => is_named(only_info),
+            None => lists.iter().flatten().any(is_named),
+        }
+    };
+
     let distro = InfoItem {
         title: "distro",
         alignment_space: 2,
@@ -174,18 +362,25 @@ fn info(
         value: uname_n(),
     };
 
+    let model = InfoItem {
+        title: "model",
+        alignment_space: 3,
+        icon: "󰌢",
+        value: get_model(options.model.bios),
+    };
+
     let shell = InfoItem {
         title: "shell",
         alignment_space: 3,
         icon: "",
-        value: shell_name(),
+        value: get_shell(options.shell.version),
     };
 
     let kernel = InfoItem {
         title: "kernel",
         alignment_space: 2,
         icon: "",
-        value: uname_r(),
+        value: get_kernel(&options.kernel.template),
     };
 
     let packs = InfoItem {
@@ -209,25 +404,57 @@ fn info(
         value: get_terminal(),
     };
 
+    let desktop_info = get_desktop();
     let de = InfoItem {
         title: "de/wm",
         alignment_space: 3,
         icon: "",
-        value: get_wm(),
+        value: desktop_info.format(),
+    };
+
+    let desktop = InfoItem {
+        title: "desktop",
+        alignment_space: 1,
+        icon: "",
+        value: desktop_info.de.clone().unwrap_or_default(),
+    };
+
+    let wm = InfoItem {
+        title: "wm",
+        alignment_space: 6,
+        icon: "",
+        value: desktop_info.format_wm(),
     };
 
     let cpu = InfoItem {
         title: "cpu",
         alignment_space: 5,
         icon: "󰍛",
-        value: get_cpu_info(),
+        value: get_cpu_info(
+            &options.cpu.template,
+            options.temperature.cpu.then_some(temp_unit),
+        ),
     };
 
     let mem = InfoItem {
         title: "mem",
         alignment_space: 5,
         icon: "",
-        value: get_mem(),
+        value: get_mem(size),
+    };
+
+    let swap = InfoItem {
+        title: "swap",
+        alignment_space: 4,
+        icon: "󰓡",
+        value: get_swap(size),
+    };
+
+    let zram = InfoItem {
+        title: "zram",
+        alignment_space: 4,
+        icon: "󰘚",
+        value: get_zram(size),
     };
 
     let res = InfoItem {
@@ -247,13 +474,66 @@ fn info(
         },
     };
 
+    // Reading GPUs can run `nvidia-smi`, so it's done once, and only if a row shows them.
+    let show_gpu = shown(&["gpu", "graphics"]);
+    let show_gpu_temp = shown(&["gputemp"]);
+    let show_temps = shown(&["temps", "temperatures"]);
+    let gpus = if show_gpu || show_gpu_temp || show_temps {
+        get_gpus()
+    } else {
+        vec![]
+    };
+    let gpu_temps = if (show_gpu && options.temperature.gpu) || show_gpu_temp || show_temps {
+        get_gpu_temps_of(&gpus)
+    } else {
+        vec![None; gpus.len()]
+    };
+
     let gpu = InfoItem {
         title: "gpu",
         alignment_space: 5,
         icon: "󰍹",
-        value: match get_gpu_info() {
-            Err(_err) => "".to_string(),
-            Ok(gpu_info) => gpu_info,
+        value: if show_gpu {
+            get_gpu_info(
+                &gpus,
+                &gpu_temps,
+                options.temperature.gpu.then_some(temp_unit),
+                size,
+            )
+            .unwrap_or_default()
+        } else {
+            String::new()
+        },
+    };
+
+    let temps = InfoItem {
+        title: "temps",
+        alignment_space: 3,
+        icon: "",
+        value: if show_temps {
+            get_temps(&gpus, &gpu_temps, temp_unit)
+        } else {
+            String::new()
+        },
+    };
+
+    let cpu_temp = InfoItem {
+        title: "cputemp",
+        alignment_space: 1,
+        icon: "",
+        value: get_cpu_temp()
+            .map(|temp| temp_unit.format(temp))
+            .unwrap_or_default(),
+    };
+
+    let gpu_temp = InfoItem {
+        title: "gputemp",
+        alignment_space: 1,
+        icon: "",
+        value: if show_gpu_temp {
+            get_gpu_temps(&gpus, &gpu_temps, temp_unit)
+        } else {
+            String::new()
         },
     };
 
@@ -261,7 +541,28 @@ fn info(
         title: "disk",
         alignment_space: 4,
         icon: "",
-        value: get_disk_usage(),
+        value: get_disk_usage(&options.disk.mounts, size),
+    };
+
+    let storage = InfoItem {
+        title: "storage",
+        alignment_space: 1,
+        icon: "󰋊",
+        value: get_storage(size),
+    };
+
+    let battery = InfoItem {
+        title: "battery",
+        alignment_space: 1,
+        icon: "󰁹",
+        value: get_battery(),
+    };
+
+    let network = InfoItem {
+        title: "network",
+        alignment_space: 1,
+        icon: "󰛳",
+        value: get_network(&options.network.interfaces, options.network.mask),
     };
 
     let timezone = InfoItem {
@@ -278,60 +579,139 @@ fn info(
         value: String::new(),
     };
 
-    let parse_info = |name: String| {
-        return match name.to_lowercase().as_str() {
-            "os" | "distro" => &distro,
-            "host" | "hostname" => &hostname,
-            "shell" => &shell,
-            "kernel" => &kernel,
-            "packs" | "packages" => &packs,
-            "user" | "username" => &user,
-            "term" | "terminal" => &term,
-            "de" | "dewm" | "wm" => &de,
-            "cpu" | "processor" => &cpu,
-            "gpu" | "graphics" => &gpu,
-            "mem" | "memory" => &mem,
-            "uptime" => &uptime,
-            "res" | "display" | "resolution" => &res,
-            "time" | "timezone" => &timezone,
-            "disk" | "diskusage" => &disk,
-            _ => &empty,
-        };
+    let parse_info = |name: String| match name.to_lowercase().as_str() {
+        "os" | "distro" => &distro,
+        "host" | "hostname" => &hostname,
+        "model" | "machine" => &model,
+        "shell" => &shell,
+        "kernel" => &kernel,
+        "packs" | "packages" => &packs,
+        "user" | "username" => &user,
+        "term" | "terminal" => &term,
+        "de" | "dewm" | "wm" => &de,
+        "desktop" => &desktop,
+        "windowmanager" => &wm,
+        "cpu" | "processor" => &cpu,
+        "gpu" | "graphics" => &gpu,
+        "mem" | "memory" => &mem,
+        "swap" => &swap,
+        "zram" => &zram,
+        "uptime" => &uptime,
+        "res" | "display" | "resolution" => &res,
+        "time" | "timezone" => &timezone,
+        "disk" | "diskusage" => &disk,
+        "storage" | "drives" => &storage,
+        "battery" | "bat" => &battery,
+        "network" | "net" => &network,
+        "temps" | "temperatures" => &temps,
+        "cputemp" => &cpu_temp,
+        "gputemp" => &gpu_temp,
+        _ => &empty,
     };
 
-    let parse_json_lists = |set| {
+    // Every module with --redact or `enabled`, otherwise only those listed in `modules`.
+    let redact_all = redact || options.redact.enabled;
+    let redactor = (redact_all || !options.redact.modules.is_empty())
+        .then(|| Redactor::new(&hostname.value, &user.value));
+    let redacted = |item: &InfoItem| {
+        let mut item = item.clone();
+        if let Some(redactor) = &redactor {
+            if redact_all
+                || options
+                    .redact
+                    .modules
+                    .iter()
+                    .any(|module| parse_info(module.clone()).title == item.title)
+            {
+                item.value = redactor.redact(&item.value, item.title);
+            }
+        }
+        item
+    };
+
+    let parse_json_lists = |list: &Vec<String>| {
         let mut info_set: Vec<InfoItem> = vec![];
-        for i in get_info(set, use_custom_info_config, custom_info_config_file.clone()) {
-            info_set.push(parse_info(i).clone());
+        for i in list {
+            info_set.push(redacted(parse_info(i.clone())));
         }
         info_set
     };
 
-    if get_only_info.is_some() {
-        return format!("{}", parse_info(get_only_info.unwrap()).value.trim_matches('"'));
+    if let Some(only_info) = get_only_info {
+        return redacted(parse_info(only_info))
+            .value
+            .trim_matches('"')
+            .to_string();
     }
 
-    let info_set1 = parse_json_lists("info1");
-    let info_set2 = parse_json_lists("info2");
-    let info_set3 = parse_json_lists("info3");
+    let info_set1 = parse_json_lists(&lists[0]);
+    let info_set2 = parse_json_lists(&lists[1]);
+    let info_set3 = parse_json_lists(&lists[2]);
 
     let margin_spaces = " ".repeat(margin as usize);
-    let distroascii = print_ascii(
-        get_distro_ascii(overriden_ascii),
-        get_color_config(
-            "color0".to_string(),
-            use_custom_color_config,
-            custom_color_config_file.clone(),
-        ),
-        !use_custom_color_config,
-        custom_color_config_file.clone(),
-    );
     let infos1 = (1, info_set1);
     let infos2 = (2, info_set2);
     let infos3 = (3, info_set3);
-    let mut info_sets = vec![infos1, infos2, infos3];
+    let mut info_sets = [infos1, infos2, infos3];
+
+    // Every non-empty row, the empty lines between the sections and the trailing one.
+    let info_height = info_sets
+        .iter()
+        .map(|infos| {
+            infos
+                .1
+                .iter()
+                .map(|item| item.value.lines().count())
+                .sum::<usize>()
+        })
+        .sum::<usize>()
+        + info_sets.len();
+
+    let image = match (&options.logo.image, logo_size) {
+        (Some(path), size) if size != LogoSize::None => match Image::load(&expand_home(path)) {
+            Ok(image) => Some(image),
+            Err(err) => {
+                println!("[{}] {}, using the ASCII logo.", "WARNING".yellow(), err);
+                None
+            }
+        },
+        _ => None,
+    };
 
-    println!("{}\n", distroascii);
+    let columns = terminal_size()
+        .map(|(columns, _)| columns.min(options.logo.width))
+        .unwrap_or(options.logo.width);
+    let custom_colors = load_custom_colors(use_custom_color_config, custom_color_config_file);
+    let mut logo = get_distro_ascii(overriden_ascii);
+    let mut image = image;
+
+    // The "ascii" protocol turns the image into a regular logo instead.
+    if options.logo.protocol.eq_ignore_ascii_case("ascii") {
+        if let Some(image) = image.take() {
+            let charset = Charset::parse(&options.logo.chars).unwrap_or(Charset::Ascii);
+            let source = to_logo_source(
+                &image,
+                &logo.name,
+                &logo.matches.join(", "),
+                columns,
+                charset,
+                MAX_COLOR_SLOTS,
+            );
+            logo = Logo::parse(&logo.id, &source);
+        }
+    }
+
+    let logo_size = match logo_size {
+        LogoSize::Auto => get_logo_size(&logo, info_height),
+        size => size,
+    };
+    if let Some(image) = image {
+        let protocol = Protocol::parse(&options.logo.protocol).unwrap_or(Protocol::Blocks);
+        println!("{}\n", render(&image, protocol, columns));
+    } else if let Some(logo) = logo.sized(logo_size) {
+        let distroascii = print_ascii(&logo, custom_colors.as_ref());
+        println!("{}\n", distroascii);
+    }
 
     for (idx, infos) in info_sets.iter_mut().enumerate() {
         if idx > 0 {
@@ -341,32 +721,29 @@ fn info(
         loop_over_data(
             &mut infos.1,
             margin_spaces.clone(),
-            infos.0,
-            use_custom_color_config,
-            custom_color_config_file.clone(),
+            get_color_config(&format!("color{}", infos.0), custom_colors.as_ref()),
         );
     }
 
     String::new()
 }
 
-fn loop_over_data(
-    list: &mut Vec<InfoItem>,
-    margin: String,
-    section: i8,
-    use_custom_config: bool,
-    custom_color_config_file: Option<String>,
-) {
+fn loop_over_data(list: &mut Vec<InfoItem>, margin: String, color: Color) {
     list.retain(|s| !s.value.is_empty());
+
+    // Modules with several values, like one per GPU, get a row for each.
+    *list = list
+        .iter()
+        .flat_map(|item| {
+            item.value.lines().map(|value| InfoItem {
+                value: value.to_string(),
+                ..item.clone()
+            })
+        })
+        .collect();
     let len = list.len();
 
     for (idx, item) in list.clone().iter().enumerate() {
-        let color = get_color_config(
-            format!("color{}", section),
-            use_custom_config,
-            custom_color_config_file.clone(),
-        );
-
         let connector = if idx == 0 {
             "╭─"
         } else if idx == len - 1 {
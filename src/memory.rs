@@ -0,0 +1,114 @@
+use crate::size::SizeFormat;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A field of /proc/meminfo in bytes, e.g. `meminfo_value(&meminfo, "MemTotal")`.
+fn meminfo_value(meminfo: &str, key: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        let kib: u64 = line
+            .strip_prefix(key)?
+            .strip_prefix(':')?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()?;
+        Some(kib * 1024)
+    })
+}
+
+fn read_meminfo() -> String {
+    fs::read_to_string("/proc/meminfo").unwrap_or_default()
+}
+
+/// Used memory the way `free` and htop count it, not counting caches that the kernel
+/// can give back.
+pub fn get_mem(size: SizeFormat) -> String {
+    let meminfo = read_meminfo();
+    let Some(total) = meminfo_value(&meminfo, "MemTotal") else {
+        return String::new();
+    };
+
+    // MemAvailable is missing before Linux 3.14 and in NetBSD's procfs.
+    let available = meminfo_value(&meminfo, "MemAvailable").unwrap_or_else(|| {
+        ["MemFree", "Buffers", "Cached", "SReclaimable"]
+            .iter()
+            .filter_map(|key| meminfo_value(&meminfo, key))
+            .sum()
+    });
+
+    size.usage(total.saturating_sub(available), total)
+}
+
+/// The `swap` module, empty when there is no swap.
+pub fn get_swap(size: SizeFormat) -> String {
+    let meminfo = read_meminfo();
+    match (
+        meminfo_value(&meminfo, "SwapTotal"),
+        meminfo_value(&meminfo, "SwapFree"),
+    ) {
+        (Some(total), Some(free)) if total > 0 => size.usage(total.saturating_sub(free), total),
+        _ => String::new(),
+    }
+}
+
+fn read_zram(device_path: &Path, size: SizeFormat) -> Option<String> {
+    let disk_size: u64 = fs::read_to_string(device_path.join("disksize"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    if disk_size == 0 {
+        return None;
+    }
+
+    // The first fields are the original and compressed size of the data stored.
+    let mm_stat: Vec<u64> = fs::read_to_string(device_path.join("mm_stat"))
+        .ok()?
+        .split_whitespace()
+        .filter_map(|field| field.parse().ok())
+        .collect();
+    let (original, compressed) = (*mm_stat.first()?, *mm_stat.get(1)?);
+
+    let usage = size.usage(original, disk_size);
+    Some(match compressed {
+        0 => usage,
+        _ => format!("{} ({:.2}x)", usage, original as f64 / compressed as f64),
+    })
+}
+
+/// Each zram device in `block_path` that has been given a disk size.
+fn get_zram_from(block_path: &Path, size: SizeFormat) -> String {
+    let Ok(entries) = fs::read_dir(block_path) else {
+        return String::new();
+    };
+
+    let mut devices: Vec<(u32, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            Some((name.strip_prefix("zram")?.parse().ok()?, entry.path()))
+        })
+        .collect();
+    devices.sort();
+
+    let zrams: Vec<(u32, String)> = devices
+        .iter()
+        .filter_map(|(number, path)| Some((*number, read_zram(path, size)?)))
+        .collect();
+
+    match zrams.as_slice() {
+        [(_, zram)] => zram.clone(),
+        zrams => zrams
+            .iter()
+            .map(|(number, zram)| format!("zram{}: {}", number, zram))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    }
+}
+
+/// The `zram` module: data stored in each zram device and how well it compressed.
+pub fn get_zram(size: SizeFormat) -> String {
+    get_zram_from(Path::new("/sys/block"), size)
+}
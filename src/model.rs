@@ -0,0 +1,271 @@
+use std::{fs, path::Path};
+
+const DMI_PATH: &str = "/sys/devices/virtual/dmi/id";
+const DEVICE_TREE_MODEL: &str = "/proc/device-tree/model";
+
+// Values firmware fills the DMI fields with when the manufacturer didn't.
+const PLACEHOLDERS: [&str; 20] = [
+    "to be filled by o.e.m.",
+    "to be filled by oem",
+    "default string",
+    "system product name",
+    "system manufacturer",
+    "system version",
+    "system name",
+    "product name",
+    "not applicable",
+    "not specified",
+    "not available",
+    "none",
+    "o.e.m.",
+    "oem",
+    "invalid",
+    "unknown",
+    "undefined",
+    "all series",
+    "type1productconfigid",
+    "0123456789",
+];
+
+// Legal names of vendors, shortened to what they go by.
+const VENDORS: [(&str, &str); 13] = [
+    ("ASUSTeK", "ASUS"),
+    ("Micro-Star", "MSI"),
+    ("Gigabyte", "Gigabyte"),
+    ("Hewlett-Packard", "HP"),
+    ("HP", "HP"),
+    ("Dell", "Dell"),
+    ("LENOVO", "Lenovo"),
+    ("Apple", "Apple"),
+    ("Microsoft", "Microsoft"),
+    ("Acer", "Acer"),
+    ("Framework", "Framework"),
+    ("Samsung", "Samsung"),
+    ("innotek", "innotek"),
+];
+
+fn read_field(dmi_path: &Path, field: &str) -> Option<String> {
+    let value = fs::read_to_string(dmi_path.join(field)).ok()?;
+    let value = value.trim();
+    (!value.is_empty() && !PLACEHOLDERS.contains(&value.to_lowercase().as_str()))
+        .then(|| value.to_string())
+}
+
+fn short_vendor(vendor: &str) -> String {
+    VENDORS
+        .iter()
+        .find(|(prefix, _)| vendor.to_lowercase().starts_with(&prefix.to_lowercase()))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| vendor.to_string())
+}
+
+// Versions like "1.0", "V2" or "REV:1.0" say nothing about which machine it is.
+fn is_revision(version: &str) -> bool {
+    let number = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    let lower = version.to_lowercase();
+    let is_number = number
+        .split('.')
+        .all(|part| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit()));
+    let is_rev = lower
+        .strip_prefix("revision")
+        .or_else(|| lower.strip_prefix("rev"))
+        .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphabetic()));
+    is_number || is_rev
+}
+
+// Product versions that name the machine like Lenovo's "ThinkPad T14 Gen 3", rather
+// than a revision or an all-caps code like "P05ABJ" or "0B98401 WIN".
+fn is_readable_version(version: &str) -> bool {
+    !is_revision(version) && version.chars().any(|c| c.is_lowercase())
+}
+
+// "Vendor Name", without repeating the vendor when the name already starts with it.
+fn with_vendor(vendor: Option<String>, name: String) -> String {
+    match vendor.map(|vendor| short_vendor(&vendor)) {
+        Some(vendor) if !name.to_lowercase().starts_with(&vendor.to_lowercase()) => {
+            format!("{} {}", vendor, name)
+        }
+        _ => name,
+    }
+}
+
+fn get_dmi_model(dmi_path: &Path) -> Option<String> {
+    let field = |field: &str| read_field(dmi_path, field);
+    let vendor = field("sys_vendor");
+
+    let version = field("product_version").filter(|version| is_readable_version(version));
+
+    let product = match (vendor.as_deref(), field("product_name"), version) {
+        // Lenovo's product name is the machine type, like "21AH00BWUS", the version
+        // is the name it's sold under.
+        (Some("LENOVO"), _, Some(version)) => Some(version),
+        (_, Some(name), Some(version)) if !name.contains(&version) => {
+            Some(format!("{} {}", name, version))
+        }
+        (_, name, _) => name,
+    };
+
+    match product {
+        Some(product) => Some(with_vendor(vendor, product)),
+        // Self-built desktops only say which motherboard they have.
+        None => Some(with_vendor(field("board_vendor"), field("board_name")?)),
+    }
+}
+
+// "Raspberry Pi 5 Model B Rev 1.0" without the board revision.
+fn get_device_tree_model(path: &Path) -> Option<String> {
+    let model = fs::read_to_string(path).ok()?;
+    let model = model.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    let model = match model.rfind(" Rev ") {
+        Some(index) => &model[..index],
+        None => model,
+    };
+    (!model.is_empty()).then(|| model.to_string())
+}
+
+/// The `model` module: the machine's vendor and model, with the BIOS version if `bios`.
+pub fn get_model(bios: bool) -> String {
+    let dmi_path = Path::new(DMI_PATH);
+    let Some(model) =
+        get_dmi_model(dmi_path).or_else(|| get_device_tree_model(Path::new(DEVICE_TREE_MODEL)))
+    else {
+        return String::new();
+    };
+
+    match read_field(dmi_path, "bios_version").filter(|_| bios) {
+        Some(version) => format!("{} (BIOS {})", model, version),
+        None => model,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fns::TestDir;
+
+    // A DMI directory with the given fields, newline-terminated like the kernel writes them.
+    fn dmi(name: &str, fields: &[(&str, &str)]) -> TestDir {
+        let path = TestDir::new(name);
+        for (field, value) in fields {
+            fs::write(path.join(field), format!("{}\n", value)).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn lenovo_is_named_by_its_version() {
+        let thinkpad = dmi(
+            "lenovo",
+            &[
+                ("sys_vendor", "LENOVO"),
+                ("product_name", "21AH00BWUS"),
+                ("product_version", "ThinkPad T14 Gen 3"),
+            ],
+        );
+        assert_eq!(
+            get_dmi_model(&thinkpad).as_deref(),
+            Some("Lenovo ThinkPad T14 Gen 3")
+        );
+
+        let unnamed = dmi(
+            "lenovo-unnamed",
+            &[
+                ("sys_vendor", "LENOVO"),
+                ("product_name", "20XW0055GE"),
+                ("product_version", "0B98401 WIN"),
+            ],
+        );
+        assert_eq!(
+            get_dmi_model(&unnamed).as_deref(),
+            Some("Lenovo 20XW0055GE")
+        );
+    }
+
+    #[test]
+    fn revisions_are_left_out() {
+        let dell = dmi(
+            "dell",
+            &[
+                ("sys_vendor", "Dell Inc."),
+                ("product_name", "XPS 13 9310"),
+                ("product_version", "1.0.4"),
+            ],
+        );
+        assert_eq!(get_dmi_model(&dell).as_deref(), Some("Dell XPS 13 9310"));
+
+        for version in ["1.0", "V2", "v1.12", "REV:1.0", "Rev 1.0", "Revision A"] {
+            let msi = dmi(
+                "msi",
+                &[
+                    ("sys_vendor", "Micro-Star International Co., Ltd."),
+                    ("product_name", "MS-7C02"),
+                    ("product_version", version),
+                ],
+            );
+            assert_eq!(
+                get_dmi_model(&msi).as_deref(),
+                Some("MSI MS-7C02"),
+                "{}",
+                version
+            );
+        }
+
+        let samsung = dmi(
+            "samsung",
+            &[
+                ("sys_vendor", "SAMSUNG ELECTRONICS CO., LTD."),
+                ("product_name", "950XDA"),
+                ("product_version", "P05ABJ"),
+            ],
+        );
+        assert_eq!(get_dmi_model(&samsung).as_deref(), Some("Samsung 950XDA"));
+
+        let hp = dmi(
+            "hp",
+            &[
+                ("sys_vendor", "HP"),
+                ("product_name", "HP EliteBook 840 G8 Notebook PC"),
+                ("product_version", "Type1ProductConfigId"),
+            ],
+        );
+        assert_eq!(
+            get_dmi_model(&hp).as_deref(),
+            Some("HP EliteBook 840 G8 Notebook PC")
+        );
+    }
+
+    #[test]
+    fn placeholders_fall_back_to_the_board() {
+        let desktop = dmi(
+            "self-built",
+            &[
+                ("sys_vendor", "System manufacturer"),
+                ("product_name", "System Product Name"),
+                ("product_version", "System Version"),
+                ("board_vendor", "ASUSTeK COMPUTER INC."),
+                ("board_name", "ROG STRIX B550-F GAMING"),
+            ],
+        );
+        assert_eq!(
+            get_dmi_model(&desktop).as_deref(),
+            Some("ASUS ROG STRIX B550-F GAMING")
+        );
+    }
+
+    #[test]
+    fn device_tree_without_revision() {
+        let device_tree = TestDir::new("device-tree");
+        let path = device_tree.join("model");
+        fs::write(&path, "Raspberry Pi 5 Model B Rev 1.0\0").unwrap();
+        assert_eq!(
+            get_device_tree_model(&path).as_deref(),
+            Some("Raspberry Pi 5 Model B")
+        );
+
+        fs::write(&path, "Pine64 PinePhone (1.2)\0").unwrap();
+        assert_eq!(
+            get_device_tree_model(&path).as_deref(),
+            Some("Pine64 PinePhone (1.2)")
+        );
+    }
+}
@@ -0,0 +1,377 @@
+use crate::fns::read_trimmed;
+use std::{
+    ffi::CStr,
+    fs, mem,
+    net::{Ipv4Addr, Ipv6Addr},
+    path::Path,
+};
+
+const NET_PATH: &str = "/sys/class/net";
+
+// Route flags from linux/route.h.
+const RTF_UP: u32 = 0x1;
+const RTF_REJECT: u32 = 0x200;
+
+// Wireless extensions' ioctl for the SSID, still answered by cfg80211 drivers.
+const SIOCGIWESSID: u64 = 0x8b1b;
+const IW_ESSID_MAX_SIZE: usize = 32;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct IwPoint {
+    pointer: *mut libc::c_void,
+    length: u16,
+    flags: u16,
+}
+
+// The part of `union iwreq_data` that's used, its largest member being a sockaddr.
+#[repr(C)]
+union IwReqData {
+    essid: IwPoint,
+    address: libc::sockaddr,
+}
+
+// `struct iwreq`, 16 bytes for the name and 16 for the union on 32 and 64 bit alike.
+#[repr(C)]
+struct IwReq {
+    name: [libc::c_char; libc::IFNAMSIZ],
+    data: IwReqData,
+}
+
+const _: () = assert!(mem::size_of::<IwReq>() == 32);
+
+#[derive(Clone, Debug, Default)]
+pub struct Interface {
+    pub name: String,
+    pub ipv4: Vec<(Ipv4Addr, u32)>,
+    pub ipv6: Vec<(Ipv6Addr, u32)>,
+    /// In Mb/s.
+    pub speed: Option<u32>,
+    pub duplex: Option<String>,
+    pub ssid: Option<String>,
+    /// In dBm.
+    pub signal: Option<i32>,
+}
+
+/// Hides all but the network part of an address, e.g. "192.168.x.x" or "2a02:810d:x".
+fn mask_ipv4(address: Ipv4Addr) -> String {
+    let [first, second, _, _] = address.octets();
+    format!("{}.{}.x.x", first, second)
+}
+
+fn mask_ipv6(address: Ipv6Addr) -> String {
+    let segments = address.segments();
+    format!("{:x}:{:x}:x", segments[0], segments[1])
+}
+
+fn format_speed(speed: u32) -> String {
+    match speed {
+        speed if speed >= 1000 => format!("{} Gbps", speed as f64 / 1000.0),
+        speed => format!("{} Mbps", speed),
+    }
+}
+
+impl Interface {
+    /// "eth0: 192.168.1.20/24, 2a02:810d::1/64 [1 Gbps, full duplex]"
+    pub fn format(&self, mask: bool) -> String {
+        let addresses: Vec<String> = self
+            .ipv4
+            .iter()
+            .map(|(address, prefix)| match mask {
+                true => mask_ipv4(*address),
+                false => format!("{}/{}", address, prefix),
+            })
+            .chain(self.ipv6.iter().map(|(address, prefix)| match mask {
+                true => mask_ipv6(*address),
+                false => format!("{}/{}", address, prefix),
+            }))
+            .collect();
+
+        let details: Vec<String> = self
+            .ssid
+            .clone()
+            .into_iter()
+            .chain(self.signal.map(|signal| format!("{} dBm", signal)))
+            .chain(self.speed.map(format_speed))
+            .chain(
+                self.duplex
+                    .as_ref()
+                    .map(|duplex| format!("{} duplex", duplex)),
+            )
+            .collect();
+
+        let mut formatted = self.name.clone();
+        if !addresses.is_empty() {
+            formatted.push_str(&format!(": {}", addresses.join(", ")));
+        }
+        if !details.is_empty() {
+            formatted.push_str(&format!(" [{}]", details.join(", ")));
+        }
+        formatted
+    }
+}
+
+/// The interfaces with a default route in /proc/net/route and /proc/net/ipv6_route,
+/// IPv4 ones first.
+fn parse_default_routes(route: &str, ipv6_route: &str) -> Vec<String> {
+    let mut interfaces: Vec<String> = vec![];
+    let mut add = |interface: &str| {
+        if interface != "lo" && !interfaces.iter().any(|found| found == interface) {
+            interfaces.push(interface.to_string());
+        }
+    };
+
+    // Iface, Destination, Gateway, Flags, ... in hex.
+    for fields in route
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+    {
+        if let [interface, "00000000", _, flags, ..] = fields.as_slice() {
+            if u32::from_str_radix(flags, 16).is_ok_and(|flags| flags & RTF_UP != 0) {
+                add(interface);
+            }
+        }
+    }
+
+    // Destination, prefix length, source, source prefix length, next hop, metric,
+    // reference count, use count, flags and interface.
+    for fields in ipv6_route
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+    {
+        if let [destination, "00", _, _, _, _, _, _, flags, interface] = fields.as_slice() {
+            let flags = u32::from_str_radix(flags, 16).unwrap_or(0);
+            if destination.bytes().all(|byte| byte == b'0')
+                && flags & RTF_UP != 0
+                && flags & RTF_REJECT == 0
+            {
+                add(interface);
+            }
+        }
+    }
+
+    interfaces
+}
+
+fn default_route_interfaces() -> Vec<String> {
+    parse_default_routes(
+        &fs::read_to_string("/proc/net/route").unwrap_or_default(),
+        &fs::read_to_string("/proc/net/ipv6_route").unwrap_or_default(),
+    )
+}
+
+fn prefix_length(netmask: &[u8]) -> u32 {
+    netmask.iter().map(|byte| byte.count_ones()).sum()
+}
+
+/// IPv4 and IPv6 addresses with their prefix lengths, per interface name.
+fn get_addresses() -> Vec<(String, Interface)> {
+    let mut addresses: Vec<(String, Interface)> = vec![];
+    let mut ifaddrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+        return addresses;
+    }
+
+    let mut current = ifaddrs;
+    while let Some(ifaddr) = unsafe { current.as_ref() } {
+        current = ifaddr.ifa_next;
+        let (Some(address), Some(netmask)) = (unsafe { ifaddr.ifa_addr.as_ref() }, unsafe {
+            ifaddr.ifa_netmask.as_ref()
+        }) else {
+            continue;
+        };
+        let name = unsafe { CStr::from_ptr(ifaddr.ifa_name) }
+            .to_string_lossy()
+            .to_string();
+        let index = match addresses.iter().position(|(found, _)| *found == name) {
+            Some(index) => index,
+            None => {
+                addresses.push((name.clone(), Interface::default()));
+                addresses.len() - 1
+            }
+        };
+        let interface = &mut addresses[index].1;
+
+        match address.sa_family as i32 {
+            libc::AF_INET => {
+                let address = unsafe { *(address as *const _ as *const libc::sockaddr_in) };
+                let netmask = unsafe { *(netmask as *const _ as *const libc::sockaddr_in) };
+                interface.ipv4.push((
+                    Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr)),
+                    prefix_length(&netmask.sin_addr.s_addr.to_ne_bytes()),
+                ));
+            }
+            libc::AF_INET6 => {
+                let address = unsafe { *(address as *const _ as *const libc::sockaddr_in6) };
+                let netmask = unsafe { *(netmask as *const _ as *const libc::sockaddr_in6) };
+                let address = Ipv6Addr::from(address.sin6_addr.s6_addr);
+                // Link-local addresses are the same on every network.
+                if address.segments()[0] & 0xffc0 != 0xfe80 {
+                    interface
+                        .ipv6
+                        .push((address, prefix_length(&netmask.sin6_addr.s6_addr)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    unsafe { libc::freeifaddrs(ifaddrs) };
+    addresses
+}
+
+fn get_ssid(interface: &str) -> Option<String> {
+    let socket = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    if socket < 0 {
+        return None;
+    }
+
+    let mut ssid = [0u8; IW_ESSID_MAX_SIZE + 1];
+    let mut request = IwReq {
+        name: [0; libc::IFNAMSIZ],
+        data: IwReqData {
+            essid: IwPoint {
+                pointer: ssid.as_mut_ptr() as *mut libc::c_void,
+                length: ssid.len() as u16,
+                flags: 0,
+            },
+        },
+    };
+    for (target, byte) in request
+        .name
+        .iter_mut()
+        .zip(interface.bytes().take(libc::IFNAMSIZ - 1))
+    {
+        *target = byte as libc::c_char;
+    }
+
+    let result = unsafe { libc::ioctl(socket, SIOCGIWESSID as _, &mut request) };
+    unsafe { libc::close(socket) };
+    if result < 0 {
+        return None;
+    }
+
+    let length = (unsafe { request.data.essid }.length as usize).min(IW_ESSID_MAX_SIZE);
+    Some(String::from_utf8_lossy(&ssid[..length]).to_string()).filter(|ssid| !ssid.is_empty())
+}
+
+// The signal level column of /proc/net/wireless, like "-56." in dBm.
+fn get_signal(interface: &str) -> Option<i32> {
+    let wireless = fs::read_to_string("/proc/net/wireless").ok()?;
+    wireless.lines().skip(2).find_map(|line| {
+        let (name, fields) = line.split_once(':')?;
+        if name.trim() != interface {
+            return None;
+        }
+        let level: f64 = fields
+            .split_whitespace()
+            .nth(2)?
+            .trim_end_matches('.')
+            .parse()
+            .ok()?;
+        Some(level as i32)
+    })
+}
+
+// Wi-Fi interfaces have a `phy80211` link, old wireless extensions drivers only the
+// `wireless` directory.
+fn is_wireless(path: &Path) -> bool {
+    path.join("phy80211").exists() || path.join("wireless").exists()
+}
+
+/// Names of the Wi-Fi networks connected to.
+pub fn get_ssids() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(NET_PATH) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| is_wireless(&entry.path()))
+        .filter_map(|entry| get_ssid(&entry.file_name().to_string_lossy()))
+        .collect()
+}
+
+/// The `network` module, one row for each interface in `interfaces`, or for those with
+/// a default route when it's empty.
+pub fn get_network(interfaces: &[String], mask: bool) -> String {
+    let names = match interfaces {
+        [] => default_route_interfaces(),
+        interfaces => interfaces.to_vec(),
+    };
+    let addresses = get_addresses();
+
+    names
+        .iter()
+        .filter(|name| Path::new(NET_PATH).join(name).exists())
+        .map(|name| {
+            let path = Path::new(NET_PATH).join(name);
+            let mut interface = addresses
+                .iter()
+                .find(|(found, _)| found == name)
+                .map(|(_, interface)| interface.clone())
+                .unwrap_or_default();
+            interface.name = name.clone();
+
+            // Reading the speed of an interface that's down fails, virtual ones say -1.
+            interface.speed = read_trimmed(&path.join("speed"))
+                .and_then(|speed| speed.parse().ok())
+                .filter(|speed: &i64| *speed > 0)
+                .map(|speed| speed as u32);
+            interface.duplex = read_trimmed(&path.join("duplex"))
+                .filter(|duplex| duplex == "full" || duplex == "half");
+
+            if is_wireless(&path) {
+                interface.ssid = get_ssid(name);
+                interface.signal = get_signal(name);
+            }
+            interface.format(mask)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTE: &str = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wg0\t00000000\t00000000\t0000\t0\t0\t50\t00000000\t0\t0\t0
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0
+wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0
+docker0\t000011AC\t00000000\t0001\t0\t0\t0\t0000FFFF\t0\t0\t0
+";
+
+    const IPV6_ROUTE: &str = "\
+2a02810d000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000258 00000001 00000000 00000001    wlan0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe80000000000000022a10fffe000001 00000064 00000002 00000000 00000003   enp5s0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe80000000000000022a10fffe000001 00000258 00000001 00000000 00000003    wlan0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+";
+
+    #[test]
+    fn default_routes() {
+        // wg0's route is down, the others aren't default routes.
+        assert_eq!(parse_default_routes(ROUTE, IPV6_ROUTE), ["wlan0", "enp5s0"]);
+        assert_eq!(parse_default_routes("", ""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn masked_addresses() {
+        assert_eq!(mask_ipv4(Ipv4Addr::new(192, 168, 1, 20)), "192.168.x.x");
+        assert_eq!(
+            mask_ipv6("2a02:810d:4b3f:c200::1".parse().unwrap()),
+            "2a02:810d:x"
+        );
+        assert_eq!(mask_ipv6("fd00::1".parse().unwrap()), "fd00:0:x");
+    }
+
+    #[test]
+    fn prefix_lengths() {
+        assert_eq!(prefix_length(&[255, 255, 255, 0]), 24);
+        assert_eq!(prefix_length(&[255, 255, 240, 0]), 20);
+        assert_eq!(prefix_length(&[0; 16]), 0);
+    }
+}
@@ -0,0 +1,72 @@
+use std::{fs, os::unix::fs::MetadataExt, path::PathBuf};
+
+// Guards against cycles in a /proc that changes while it's walked.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct Process {
+    pub pid: u32,
+    /// The name of the executable, cut to 15 characters by the kernel.
+    pub comm: String,
+}
+
+impl Process {
+    pub fn exe(&self) -> Option<PathBuf> {
+        fs::read_link(format!("/proc/{}/exe", self.pid)).ok()
+    }
+
+    /// The user the process runs as, going by who owns its /proc directory.
+    pub fn uid(&self) -> Option<u32> {
+        fs::metadata(format!("/proc/{}", self.pid))
+            .ok()
+            .map(|metadata| metadata.uid())
+    }
+
+    /// The login session the process belongs to, the same ID logind puts in
+    /// `XDG_SESSION_ID`. `None` for processes outside of any session, like user services.
+    pub fn session(&self) -> Option<String> {
+        let session = fs::read_to_string(format!("/proc/{}/sessionid", self.pid)).ok()?;
+        let session = session.trim();
+        // (u32)-1 means unset.
+        (session != "4294967295").then(|| session.to_string())
+    }
+}
+
+/// The parent of `pid`, from /proc/<pid>/stat.
+pub fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The name in brackets can contain spaces and brackets itself, the fields after the
+    // last ")" are the state and then the parent's PID.
+    stat[stat.rfind(')')? + 1..]
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+pub fn get_process(pid: u32) -> Option<Process> {
+    let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(Process {
+        pid,
+        comm: comm.trim().to_string(),
+    })
+}
+
+/// `pid` and every process above it, up to but not including init.
+pub fn ancestors(pid: u32) -> Vec<Process> {
+    let mut ancestors = vec![];
+    let mut pid = pid;
+    while pid > 1 && ancestors.len() < MAX_DEPTH {
+        let Some(process) = get_process(pid) else {
+            break;
+        };
+        ancestors.push(process);
+        pid = parent_pid(pid).unwrap_or(0);
+    }
+    ancestors
+}
+
+/// Every process above this one.
+pub fn our_ancestors() -> Vec<Process> {
+    ancestors(parent_pid(std::process::id()).unwrap_or(0))
+}
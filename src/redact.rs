@@ -0,0 +1,244 @@
+use crate::network::get_ssids;
+use std::{fs, net::Ipv6Addr, path::Path};
+
+// Files that hold serial numbers, most of them only readable by root.
+const SERIAL_FILES: [&str; 4] = [
+    "/sys/class/dmi/id/product_serial",
+    "/sys/class/dmi/id/board_serial",
+    "/sys/class/dmi/id/chassis_serial",
+    "/proc/device-tree/serial-number",
+];
+
+// Characters around addresses that aren't part of them, like in "[192.168.1.20, ...]".
+const PUNCTUATION: [char; 7] = [',', ';', '[', ']', '(', ')', '"'];
+
+/// Masks personal details in the values of modules: Wi-Fi network names and serial
+/// numbers as they are, anything that looks like a MAC address, and the hostname,
+/// username and IP addresses in the modules that show them.
+pub struct Redactor {
+    hostname: String,
+    username: String,
+    literals: Vec<(String, &'static str)>,
+}
+
+fn read_serials() -> Vec<String> {
+    let drive_serials = fs::read_dir("/sys/block")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("device/serial"));
+
+    SERIAL_FILES
+        .iter()
+        .map(|path| Path::new(path).to_path_buf())
+        .chain(drive_serials)
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|serial| {
+            serial
+                .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+                .to_string()
+        })
+        // Leaves out placeholders like "0" or "Default string".
+        .filter(|serial| {
+            serial.len() >= 4
+                && serial.bytes().any(|byte| byte.is_ascii_digit())
+                && !serial.contains(' ')
+        })
+        .collect()
+}
+
+// Four decimal octets up to 255, without leading zeros, so "1.2.300.4" isn't one.
+fn is_ipv4(word: &str) -> bool {
+    let octets: Vec<&str> = word.split('.').collect();
+    octets.len() == 4
+        && octets.iter().all(|octet| {
+            !octet.is_empty()
+                && octet.len() <= 3
+                && octet.bytes().all(|byte| byte.is_ascii_digit())
+                && (octet.len() == 1 || !octet.starts_with('0'))
+                && octet.parse::<u16>().is_ok_and(|octet| octet <= 255)
+        })
+}
+
+fn is_mac(word: &str) -> bool {
+    let octets: Vec<&str> = word.split([':', '-']).collect();
+    octets.len() == 6
+        && octets
+            .iter()
+            .all(|octet| octet.len() == 2 && octet.bytes().all(|byte| byte.is_ascii_hexdigit()))
+}
+
+// Whether `text[start..end]` isn't part of a longer word.
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    !is_word(text[..start].chars().next_back()) && !is_word(text[end..].chars().next())
+}
+
+// Replaces `word` wherever `is_match` holds for where it is in `text`.
+fn replace_where(
+    text: &str,
+    word: &str,
+    replacement: &str,
+    is_match: impl Fn(usize, usize) -> bool,
+) -> String {
+    if word.is_empty() {
+        return text.to_string();
+    }
+
+    let mut replaced = String::new();
+    let mut rest = text;
+    let mut offset = 0;
+    while let Some(start) = rest.find(word) {
+        let end = start + word.len();
+        replaced.push_str(&rest[..start]);
+        if is_match(offset + start, offset + end) {
+            replaced.push_str(replacement);
+        } else {
+            replaced.push_str(word);
+        }
+        rest = &rest[end..];
+        offset += end;
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+fn replace_words(text: &str, word: &str, replacement: &str) -> String {
+    replace_where(text, word, replacement, |start, end| {
+        is_whole_word(text, start, end)
+    })
+}
+
+// Only whole path components, like the username in "/run/media/user/USB".
+fn replace_path_components(text: &str, word: &str, replacement: &str) -> String {
+    replace_where(text, word, replacement, |start, end| {
+        text[..start].ends_with('/')
+            && !text[end..].starts_with(|c: char| c.is_alphanumeric() || "_-.".contains(c))
+    })
+}
+
+impl Redactor {
+    /// `hostname` and `username` as the `host` and `user` modules show them.
+    pub fn new(hostname: &str, username: &str) -> Redactor {
+        let mut literals: Vec<(String, &'static str)> = get_ssids()
+            .into_iter()
+            .map(|ssid| (ssid, "<ssid>"))
+            .collect();
+        literals.extend(
+            read_serials()
+                .into_iter()
+                .map(|serial| (serial, "<serial>")),
+        );
+        literals.retain(|(literal, _)| !literal.is_empty());
+        // Longer ones first, in case one contains another.
+        literals.sort_by_key(|(literal, _)| std::cmp::Reverse(literal.len()));
+
+        Redactor {
+            hostname: hostname.to_string(),
+            username: username.to_string(),
+            literals,
+        }
+    }
+
+    fn redact_word(word: &str, ips: bool) -> String {
+        let address = word.trim_matches(PUNCTUATION);
+        // Without the prefix length, like "/24".
+        let without_prefix = address.split('/').next().unwrap_or(address);
+        let is_ip = is_ipv4(without_prefix) || without_prefix.parse::<Ipv6Addr>().is_ok();
+        let replacement = if ips && is_ip {
+            "<ip>"
+        } else if is_mac(address) {
+            "<mac>"
+        } else {
+            return word.to_string();
+        };
+        word.replacen(address, replacement, 1)
+    }
+
+    /// Redacts the value of the module titled `title`. Outside the modules that show them,
+    /// things like "1.2.3.4" are more likely versions, and a hostname like "arch" more
+    /// likely part of a distro's name.
+    pub fn redact(&self, value: &str, title: &str) -> String {
+        let ips = matches!(title, "network" | "host");
+        let mut redacted = value
+            .split('\n')
+            .map(|line| {
+                line.split(' ')
+                    .map(|word| Redactor::redact_word(word, ips))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        for (literal, replacement) in &self.literals {
+            redacted = replace_words(&redacted, literal, replacement);
+        }
+        match title {
+            "host" => replace_words(&redacted, &self.hostname, "<hostname>"),
+            "user" => replace_words(&redacted, &self.username, "<user>"),
+            _ => replace_path_components(&redacted, &self.username, "<user>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(hostname: &str, username: &str) -> Redactor {
+        Redactor {
+            hostname: hostname.to_string(),
+            username: username.to_string(),
+            literals: vec![("S/N 1234".to_string(), "<serial>")],
+        }
+    }
+
+    #[test]
+    fn ips_only_where_asked_for() {
+        let redactor = redactor("", "");
+        assert_eq!(
+            redactor.redact(
+                "wlan0: 192.168.1.20/24, fe80::1 (aa:bb:cc:dd:ee:ff)",
+                "network"
+            ),
+            "wlan0: <ip>, <ip> (<mac>)"
+        );
+        assert_eq!(
+            redactor.redact("Firmware 1.2.3.4 (aa:bb:cc:dd:ee:ff)", "kernel"),
+            "Firmware 1.2.3.4 (<mac>)"
+        );
+    }
+
+    #[test]
+    fn names_only_where_shown() {
+        let redactor = redactor("arch", "linux");
+        assert_eq!(redactor.redact("arch", "host"), "<hostname>");
+        assert_eq!(redactor.redact("linux", "user"), "<user>");
+        assert_eq!(redactor.redact("arch linux", "distro"), "arch linux");
+        assert_eq!(
+            redactor.redact("6.9.1-arch1-1 (linux)", "kernel"),
+            "6.9.1-arch1-1 (linux)"
+        );
+        // Except in paths, like those of removable drives.
+        assert_eq!(
+            redactor.redact(
+                "/ (ext4), /run/media/linux/USB (vfat), /mnt/linux-iso",
+                "disk"
+            ),
+            "/ (ext4), /run/media/<user>/USB (vfat), /mnt/linux-iso"
+        );
+        assert_eq!(redactor.redact("S/N 1234", "model"), "<serial>");
+    }
+
+    #[test]
+    fn ipv4_octets() {
+        assert!(is_ipv4("10.0.0.1"));
+        assert!(is_ipv4("255.255.255.255"));
+        assert!(!is_ipv4("1.2.300.4"));
+        assert!(!is_ipv4("1.2.03.4"));
+        assert!(!is_ipv4("1.2.3"));
+        assert!(!is_ipv4("1.2.3.4.5"));
+        assert!(!is_ipv4("1..3.4"));
+    }
+}
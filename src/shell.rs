@@ -0,0 +1,82 @@
+use crate::process::our_ancestors;
+use crate::version::{cached_version, parse_version};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+// Shell name, the variable some of them export their version in, and whether it's safe
+// to run with `--version` (dash and plain sh don't have one).
+const SHELLS: [(&str, Option<&str>, bool); 18] = [
+    ("bash", Some("BASH_VERSION"), true),
+    ("zsh", Some("ZSH_VERSION"), true),
+    ("fish", Some("FISH_VERSION"), true),
+    ("nu", Some("NU_VERSION"), true),
+    ("ksh", Some("KSH_VERSION"), true),
+    ("mksh", Some("KSH_VERSION"), false),
+    ("oksh", Some("KSH_VERSION"), false),
+    ("tcsh", None, true),
+    ("csh", None, false),
+    ("dash", None, false),
+    ("sh", None, false),
+    ("ash", None, false),
+    ("yash", None, true),
+    ("elvish", None, false),
+    ("xonsh", None, true),
+    ("ion", None, true),
+    ("pwsh", None, true),
+    ("osh", None, true),
+];
+
+/// The shell rsftch was started from, going up the process tree past anything that
+/// isn't a shell (like `cargo run` or `sudo`), then `$SHELL`.
+pub fn get_shell(show_version: bool) -> String {
+    let shell_name = |path: &Path| {
+        let name = path.file_name()?.to_string_lossy().to_string();
+        SHELLS
+            .iter()
+            .any(|(shell, _, _)| *shell == name)
+            .then_some(name)
+    };
+
+    let running = our_ancestors().iter().find_map(|process| {
+        let exe = process.exe();
+        // The executable can be unreadable, e.g. for another user's process.
+        let name = exe
+            .as_deref()
+            .and_then(shell_name)
+            .or_else(|| shell_name(Path::new(&process.comm)))?;
+        Some((name, exe))
+    });
+    let Some((name, exe)) = running.or_else(|| {
+        let shell = PathBuf::from(env::var("SHELL").ok()?);
+        Some((shell_name(&shell)?, Some(shell)))
+    }) else {
+        return env::var("SHELL")
+            .ok()
+            .and_then(|shell| Some(Path::new(&shell).file_name()?.to_string_lossy().to_string()))
+            .unwrap_or_default();
+    };
+
+    if !show_version {
+        return name;
+    }
+
+    let (_, version_var, runnable) = SHELLS
+        .iter()
+        .find(|(shell, _, _)| *shell == name)
+        .copied()
+        .unwrap_or_default();
+    let version = version_var
+        .and_then(|var| env::var(var).ok())
+        .and_then(|version| parse_version(&version))
+        .or_else(|| match runnable {
+            true => cached_version(exe.as_deref()?, "--version"),
+            false => None,
+        });
+
+    match version {
+        Some(version) => format!("{} {}", name, version),
+        None => name,
+    }
+}
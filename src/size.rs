@@ -0,0 +1,152 @@
+const BINARY_UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+const DECIMAL_UNITS: [&str; 6] = ["B", "kB", "MB", "GB", "TB", "PB"];
+
+/// How byte sizes are shown, shared by every module that prints one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeFormat {
+    pub decimal: bool,
+    pub precision: usize,
+    /// Power of the base to show sizes in, picked for each size when `None`.
+    pub exponent: Option<u32>,
+}
+
+impl SizeFormat {
+    /// `units` is "binary" or "decimal" and `unit` either "auto" or a unit like "MiB" or
+    /// "GB", which then also decides between binary and decimal units. `None` for any
+    /// other value.
+    pub fn parse(units: &str, precision: usize, unit: &str) -> Option<SizeFormat> {
+        let decimal = match units.to_ascii_lowercase().as_str() {
+            "binary" => false,
+            "decimal" => true,
+            _ => return None,
+        };
+
+        let unit = unit.trim();
+        if unit.eq_ignore_ascii_case("auto") {
+            return Some(SizeFormat {
+                decimal,
+                precision,
+                exponent: None,
+            });
+        }
+        [BINARY_UNITS, DECIMAL_UNITS].iter().find_map(|names| {
+            let exponent = names
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(unit))?;
+            Some(SizeFormat {
+                decimal: names == &DECIMAL_UNITS,
+                precision,
+                exponent: Some(exponent as u32),
+            })
+        })
+    }
+
+    fn base(self) -> f64 {
+        if self.decimal {
+            1000.0
+        } else {
+            1024.0
+        }
+    }
+
+    // The largest unit `bytes` is at least one of, MiB below a GiB and so on.
+    fn exponent_for(self, bytes: u64) -> u32 {
+        self.exponent.unwrap_or_else(|| {
+            let mut exponent = 0;
+            let mut scaled = bytes as f64;
+            while scaled >= self.base() && exponent + 1 < BINARY_UNITS.len() as u32 {
+                scaled /= self.base();
+                exponent += 1;
+            }
+            exponent
+        })
+    }
+
+    fn format_in(self, bytes: u64, exponent: u32) -> String {
+        let units = if self.decimal {
+            DECIMAL_UNITS
+        } else {
+            BINARY_UNITS
+        };
+        // There's no such thing as half a byte.
+        let precision = if exponent == 0 { 0 } else { self.precision };
+        format!(
+            "{:.*} {}",
+            precision,
+            bytes as f64 / self.base().powi(exponent as i32),
+            units[exponent as usize]
+        )
+    }
+
+    pub fn format(self, bytes: u64) -> String {
+        self.format_in(bytes, self.exponent_for(bytes))
+    }
+
+    /// "used / total", both in the unit that suits the total.
+    pub fn usage(self, used: u64, total: u64) -> String {
+        let exponent = self.exponent_for(total);
+        format!(
+            "{} / {}",
+            self.format_in(used, exponent),
+            self.format_in(total, exponent)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let format = |decimal, exponent| SizeFormat {
+            decimal,
+            precision: 2,
+            exponent,
+        };
+        assert_eq!(
+            SizeFormat::parse("binary", 2, "auto"),
+            Some(format(false, None))
+        );
+        assert_eq!(
+            SizeFormat::parse("Decimal", 2, "AUTO"),
+            Some(format(true, None))
+        );
+        // The unit decides between binary and decimal units.
+        assert_eq!(
+            SizeFormat::parse("binary", 2, "GB"),
+            Some(format(true, Some(3)))
+        );
+        assert_eq!(
+            SizeFormat::parse("decimal", 2, " mib "),
+            Some(format(false, Some(2)))
+        );
+        assert_eq!(SizeFormat::parse("metric", 2, "auto"), None);
+        assert_eq!(SizeFormat::parse("binary", 2, "GiBs"), None);
+    }
+
+    #[test]
+    fn format() {
+        let binary = SizeFormat::parse("binary", 2, "auto").unwrap();
+        let decimal = SizeFormat::parse("decimal", 1, "auto").unwrap();
+        assert_eq!(binary.format(512), "512 B");
+        assert_eq!(binary.format(1536), "1.50 KiB");
+        assert_eq!(binary.format(8 * 1024 * 1024 * 1024), "8.00 GiB");
+        assert_eq!(decimal.format(1_500_000_000), "1.5 GB");
+        // Nothing's bigger than a PiB.
+        assert_eq!(binary.format(2 << 60), "2048.00 PiB");
+
+        let mib = SizeFormat::parse("binary", 0, "MiB").unwrap();
+        assert_eq!(mib.format(8 * 1024 * 1024 * 1024), "8192 MiB");
+        assert_eq!(mib.format(100), "0 MiB");
+    }
+
+    #[test]
+    fn usage_in_the_unit_of_the_total() {
+        let binary = SizeFormat::parse("binary", 2, "auto").unwrap();
+        assert_eq!(
+            binary.usage(512 * 1024 * 1024, 16 * 1024 * 1024 * 1024),
+            "0.50 GiB / 16.00 GiB"
+        );
+    }
+}
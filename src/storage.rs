@@ -0,0 +1,226 @@
+use crate::fns::{bracketed, read_trimmed};
+use crate::size::SizeFormat;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Virtual block devices that don't stand for a physical disk.
+const VIRTUAL_PREFIXES: [&str; 6] = ["loop", "ram", "zram", "dm-", "md", "nbd"];
+
+#[derive(Clone, Debug)]
+pub struct Drive {
+    pub name: String,
+    pub model: Option<String>,
+    pub size: u64,
+    pub kind: Option<&'static str>,
+    pub transport: Option<&'static str>,
+    pub removable: bool,
+}
+
+impl Drive {
+    /// The "[SSD, SATA, removable]" part shown after the drive.
+    pub fn details(&self) -> String {
+        bracketed(
+            self.kind
+                .into_iter()
+                .chain(self.transport)
+                .chain(self.removable.then_some("removable")),
+        )
+    }
+}
+
+// How the drive is connected, going by the devices it hangs off of.
+fn transport(name: &str, device_path: &Path) -> Option<&'static str> {
+    let path = device_path.to_string_lossy();
+    if path.contains("/usb") {
+        Some("USB")
+    } else if name.starts_with("nvme") {
+        Some("PCIe")
+    } else if path.contains("/virtio") {
+        Some("virtio")
+    } else if name.starts_with("mmcblk") {
+        Some("MMC")
+    } else if path.contains("/ata") {
+        Some("SATA")
+    } else if path.contains("/host") {
+        Some("SCSI")
+    } else {
+        None
+    }
+}
+
+fn read_drive(block_path: &Path) -> Option<Drive> {
+    let name = block_path.file_name()?.to_string_lossy().to_string();
+    if VIRTUAL_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        return None;
+    }
+
+    // Always in 512 byte sectors, whatever the drive's sector size.
+    let sectors: u64 = read_trimmed(&block_path.join("size"))?.parse().ok()?;
+    if sectors == 0 {
+        return None;
+    }
+
+    let device = block_path.join("device");
+    let model = read_trimmed(&device.join("model"))
+        .or_else(|| read_trimmed(&device.join("name")))
+        .map(|model| model.split_whitespace().collect::<Vec<&str>>().join(" "));
+
+    let device_path = fs::canonicalize(block_path).unwrap_or_else(|_| block_path.to_path_buf());
+    let transport = transport(&name, &device_path);
+    let rotational = read_trimmed(&block_path.join("queue/rotational"));
+    let kind = match (name.as_str(), transport) {
+        (name, _) if name.starts_with("nvme") => Some("NVMe"),
+        (name, _) if name.starts_with("mmcblk") => {
+            match read_trimmed(&device.join("type")).as_deref() {
+                Some("SD") => Some("SD"),
+                _ => Some("eMMC"),
+            }
+        }
+        // Virtual disks report whatever the hypervisor chose.
+        (_, Some("virtio")) => None,
+        _ => match rotational.as_deref() {
+            Some("1") => Some("HDD"),
+            Some("0") => Some("SSD"),
+            _ => None,
+        },
+    };
+
+    Some(Drive {
+        model,
+        size: sectors * 512,
+        kind,
+        transport,
+        removable: read_trimmed(&block_path.join("removable")).as_deref() == Some("1"),
+        name,
+    })
+}
+
+/// The drives in `block_path`, without loop, RAM and other virtual block devices.
+fn get_drives_from(block_path: &Path) -> Vec<Drive> {
+    let Ok(entries) = fs::read_dir(block_path) else {
+        return vec![];
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+
+    paths.iter().filter_map(|path| read_drive(path)).collect()
+}
+
+/// The `storage` module: model, size and type of every drive.
+pub fn get_storage(size: SizeFormat) -> String {
+    get_drives_from(Path::new("/sys/block"))
+        .iter()
+        .map(|drive| {
+            let mut info = format!(
+                "{} ({})",
+                drive.model.as_deref().unwrap_or(&drive.name),
+                size.format(drive.size)
+            );
+            let details = drive.details();
+            if !details.is_empty() {
+                info.push_str(&format!(" {}", details));
+            }
+            info
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fns::TestDir;
+    use std::os::unix::fs::symlink;
+
+    // A block device at `path`, with the given files in it.
+    fn block(path: &Path, files: &[(&str, &str)]) {
+        for (file, value) in files {
+            let file = path.join(file);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn drives_from_sys_block() {
+        let root = TestDir::new("sys-block");
+        let block_path = root.join("block");
+        for name in ["loop0", "ram0", "zram0", "dm-0", "md127", "nbd0"] {
+            block(&block_path.join(name), &[("size", "2097152")]);
+        }
+        // A card reader without a card in it.
+        block(&block_path.join("sdb"), &[("size", "0")]);
+        block(
+            &block_path.join("nvme0n1"),
+            &[
+                ("size", "1953525168"),
+                ("device/model", "Samsung SSD 980 PRO 1TB             "),
+                ("queue/rotational", "0"),
+            ],
+        );
+        block(
+            &block_path.join("mmcblk0"),
+            &[
+                ("size", "124735488"),
+                ("device/name", "SC64G"),
+                ("device/type", "SD"),
+                ("removable", "1"),
+            ],
+        );
+        // Linked to where it sits in the device tree, like in /sys/block.
+        let sda =
+            root.join("devices/pci0000:00/0000:00:17.0/ata1/host0/target0:0:0/0:0:0:0/block/sda");
+        block(
+            &sda,
+            &[
+                ("size", "3907029168"),
+                ("device/model", "WDC  WD20EZAZ-00G"),
+                ("queue/rotational", "1"),
+                ("removable", "0"),
+            ],
+        );
+        symlink(&sda, block_path.join("sda")).unwrap();
+
+        let drives: Vec<(String, Option<String>, u64, String)> = get_drives_from(&block_path)
+            .into_iter()
+            .map(|drive| {
+                (
+                    drive.name.clone(),
+                    drive.model.clone(),
+                    drive.size,
+                    drive.details(),
+                )
+            })
+            .collect();
+        let drive = |name: &str, model: &str, size, details: &str| {
+            (
+                name.to_string(),
+                Some(model.to_string()),
+                size,
+                details.to_string(),
+            )
+        };
+        assert_eq!(
+            drives,
+            [
+                drive("mmcblk0", "SC64G", 63864569856, "[SD, MMC, removable]"),
+                drive(
+                    "nvme0n1",
+                    "Samsung SSD 980 PRO 1TB",
+                    1000204886016,
+                    "[NVMe, PCIe]"
+                ),
+                drive("sda", "WDC WD20EZAZ-00G", 2000398934016, "[HDD, SATA]"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use crate::fns::read_trimmed;
+use crate::gpu::Gpu;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const CPU_CHIPS: [&str; 7] = [
+    "k10temp",
+    "coretemp",
+    "zenpower",
+    "cpu_thermal",
+    "cpu-thermal",
+    "soc_thermal",
+    "via_cputemp",
+];
+const GPU_CHIPS: [&str; 5] = ["amdgpu", "radeon", "nouveau", "i915", "xe"];
+const DRIVE_CHIPS: [&str; 2] = ["nvme", "drivetemp"];
+
+// The label that best represents each chip, the first input is used otherwise.
+const PREFERRED_LABELS: [&str; 6] = ["Tdie", "Tctl", "Package id 0", "edge", "Composite", "CPU"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TempUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TempUnit {
+    pub fn parse(unit: &str) -> Option<TempUnit> {
+        match unit.to_ascii_lowercase().as_str() {
+            "celsius" | "c" => Some(TempUnit::Celsius),
+            "fahrenheit" | "f" => Some(TempUnit::Fahrenheit),
+            "kelvin" | "k" => Some(TempUnit::Kelvin),
+            _ => None,
+        }
+    }
+
+    pub fn format(self, celsius: f64) -> String {
+        match self {
+            TempUnit::Celsius => format!("{:.1}°C", celsius),
+            TempUnit::Fahrenheit => format!("{:.1}°F", celsius * 9.0 / 5.0 + 32.0),
+            TempUnit::Kelvin => format!("{:.1} K", celsius + 273.15),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SensorKind {
+    Cpu,
+    Gpu,
+    Drive,
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub struct Sensor {
+    pub chip: String,
+    pub kind: SensorKind,
+    pub celsius: f64,
+    /// The device the chip belongs to, e.g. the PCI device of a GPU.
+    pub device_path: Option<PathBuf>,
+}
+
+impl Sensor {
+    // Drives go by their block device, like "sda", or NVMe controller, like "nvme0".
+    fn device_name(&self) -> Option<String> {
+        let device_path = self.device_path.as_ref()?;
+        fs::read_dir(device_path.join("block"))
+            .ok()
+            .and_then(|mut entries| entries.find_map(|entry| entry.ok()))
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .or_else(|| Some(device_path.file_name()?.to_string_lossy().to_string()))
+    }
+
+    pub fn label(&self) -> String {
+        match (self.kind, self.chip.as_str()) {
+            (SensorKind::Cpu, _) => "CPU".to_string(),
+            (SensorKind::Gpu, _) => "GPU".to_string(),
+            (SensorKind::Drive, "nvme") => self.device_name().unwrap_or_else(|| "NVMe".to_string()),
+            (SensorKind::Drive, _) => self.device_name().unwrap_or_else(|| "Disk".to_string()),
+            (SensorKind::Other, chip) => chip.to_string(),
+        }
+    }
+}
+
+// One sensor per chip, picked from its `tempN_input` files.
+fn read_chip(hwmon_path: &Path) -> Option<Sensor> {
+    let chip = read_trimmed(&hwmon_path.join("name"))?;
+    let kind = if CPU_CHIPS.contains(&chip.as_str()) {
+        SensorKind::Cpu
+    } else if GPU_CHIPS.contains(&chip.as_str()) {
+        SensorKind::Gpu
+    } else if DRIVE_CHIPS.contains(&chip.as_str()) {
+        SensorKind::Drive
+    } else {
+        SensorKind::Other
+    };
+
+    let mut inputs: Vec<(u32, String, f64)> = fs::read_dir(hwmon_path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let number: u32 = name
+                .strip_prefix("temp")?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()?;
+            let millidegrees: f64 = read_trimmed(&entry.path())?.parse().ok()?;
+            let label = read_trimmed(&hwmon_path.join(format!("temp{}_label", number)))
+                .unwrap_or_else(|| format!("temp{}", number));
+            Some((number, label, millidegrees / 1000.0))
+        })
+        .collect();
+    inputs.sort_by_key(|(number, _, _)| *number);
+
+    let (_, _, celsius) = PREFERRED_LABELS
+        .iter()
+        .find_map(|preferred| inputs.iter().find(|(_, label, _)| label == preferred))
+        .or(inputs.first())
+        .cloned()?;
+
+    Some(Sensor {
+        chip,
+        kind,
+        celsius,
+        device_path: fs::canonicalize(hwmon_path.join("device")).ok(),
+    })
+}
+
+/// Reads the hwmon directories in `hwmon_path` in order, hwmon2 before hwmon10.
+fn get_sensors_from(hwmon_path: &Path) -> Vec<Sensor> {
+    let Ok(entries) = fs::read_dir(hwmon_path) else {
+        return vec![];
+    };
+
+    let mut hwmons: Vec<(u32, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            Some((name.strip_prefix("hwmon")?.parse().ok()?, entry.path()))
+        })
+        .collect();
+    hwmons.sort();
+
+    hwmons
+        .iter()
+        .filter_map(|(_, path)| read_chip(path))
+        .collect()
+}
+
+pub fn get_sensors() -> Vec<Sensor> {
+    get_sensors_from(Path::new("/sys/class/hwmon"))
+}
+
+pub fn cpu_temp(sensors: &[Sensor]) -> Option<f64> {
+    sensors
+        .iter()
+        .find(|sensor| sensor.kind == SensorKind::Cpu)
+        .map(|sensor| sensor.celsius)
+}
+
+/// Temperature of the chip belonging to `device_path`, e.g. a GPU's PCI device.
+pub fn device_temp(sensors: &[Sensor], device_path: &Path) -> Option<f64> {
+    let device_path = fs::canonicalize(device_path).ok()?;
+    sensors
+        .iter()
+        .find(|sensor| sensor.device_path.as_ref() == Some(&device_path))
+        .map(|sensor| sensor.celsius)
+}
+
+fn format_temps(
+    sensors: &[Sensor],
+    gpus: &[Gpu],
+    gpu_temps: &[Option<f64>],
+    unit: TempUnit,
+) -> String {
+    let of_kind = |kind| sensors.iter().filter(move |sensor| sensor.kind == kind);
+    let gpu_paths: Vec<PathBuf> = gpus
+        .iter()
+        .filter_map(|gpu| fs::canonicalize(&gpu.device_path).ok())
+        .collect();
+
+    let cpus = of_kind(SensorKind::Cpu).map(|sensor| (sensor.label(), sensor.celsius));
+    // From `get_gpu_temps_of`, so GPUs without a hwmon chip, like NVIDIA's, are included.
+    let listed_gpus = gpus
+        .iter()
+        .zip(gpu_temps)
+        .filter_map(|(gpu, temp)| Some((gpu.name.clone(), (*temp)?)));
+    let unlisted_gpus = of_kind(SensorKind::Gpu)
+        .filter(|sensor| {
+            !sensor
+                .device_path
+                .as_ref()
+                .is_some_and(|path| gpu_paths.contains(path))
+        })
+        .map(|sensor| (sensor.label(), sensor.celsius));
+    let drives = of_kind(SensorKind::Drive).map(|sensor| (sensor.label(), sensor.celsius));
+
+    cpus.chain(listed_gpus)
+        .chain(unlisted_gpus)
+        .chain(drives)
+        .map(|(label, celsius)| format!("{} {}", label, unit.format(celsius)))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// The `temps` module: CPU, GPU and drive temperatures, each named after its device.
+/// `gpu_temps` are those of `gpus`, from `get_gpu_temps_of`.
+pub fn get_temps(gpus: &[Gpu], gpu_temps: &[Option<f64>], unit: TempUnit) -> String {
+    format_temps(&get_sensors(), gpus, gpu_temps, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fns::TestDir;
+    use crate::gpu::GpuKind;
+    use std::os::unix::fs::symlink;
+
+    // A `hwmonN` directory with a `tempN_input` and `tempN_label` for each input, and a
+    // `device` link when `device` is given.
+    fn hwmon(
+        hwmon_path: &Path,
+        hwmon: &str,
+        chip: &str,
+        inputs: &[(&str, &str)],
+        device: Option<&Path>,
+    ) {
+        let path = hwmon_path.join(hwmon);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("name"), format!("{}\n", chip)).unwrap();
+        for (number, (label, millidegrees)) in inputs.iter().enumerate() {
+            fs::write(path.join(format!("temp{}_input", number + 1)), millidegrees).unwrap();
+            fs::write(path.join(format!("temp{}_label", number + 1)), label).unwrap();
+        }
+        if let Some(device) = device {
+            fs::create_dir_all(device).unwrap();
+            symlink(device, path.join("device")).unwrap();
+        }
+    }
+
+    // A CPU, an AMD GPU, an NVMe drive, a SATA drive and an ACPI thermal zone.
+    fn sensors_fixture(root: &Path) -> PathBuf {
+        let hwmon_path = root.join("hwmon");
+        let devices = root.join("devices");
+        hwmon(
+            &hwmon_path,
+            "hwmon0",
+            "k10temp",
+            &[("Tctl", "52000"), ("Tdie", "48500")],
+            None,
+        );
+        hwmon(
+            &hwmon_path,
+            "hwmon2",
+            "nvme",
+            &[("Composite", "38900"), ("Sensor 1", "44900")],
+            Some(&devices.join("nvme0")),
+        );
+        hwmon(&hwmon_path, "hwmon3", "acpitz", &[("temp1", "27800")], None);
+        hwmon(
+            &hwmon_path,
+            "hwmon4",
+            "amdgpu",
+            &[("edge", "50000"), ("junction", "58000")],
+            Some(&devices.join("0000:03:00.0")),
+        );
+        hwmon(
+            &hwmon_path,
+            "hwmon10",
+            "drivetemp",
+            &[("temp1", "35000")],
+            Some(&devices.join("0:0:0:0")),
+        );
+        fs::create_dir_all(devices.join("0:0:0:0/block/sda")).unwrap();
+        hwmon_path
+    }
+
+    #[test]
+    fn sensors_are_read_in_order() {
+        let root = TestDir::new("hwmon");
+        let sensors = get_sensors_from(&sensors_fixture(&root));
+
+        let chips: Vec<(&str, SensorKind, f64)> = sensors
+            .iter()
+            .map(|sensor| (sensor.chip.as_str(), sensor.kind, sensor.celsius))
+            .collect();
+        assert_eq!(
+            chips,
+            [
+                ("k10temp", SensorKind::Cpu, 48.5),
+                ("nvme", SensorKind::Drive, 38.9),
+                ("acpitz", SensorKind::Other, 27.8),
+                ("amdgpu", SensorKind::Gpu, 50.0),
+                ("drivetemp", SensorKind::Drive, 35.0),
+            ]
+        );
+        assert_eq!(sensors[1].label(), "nvme0");
+        assert_eq!(sensors[4].label(), "sda");
+    }
+
+    #[test]
+    fn temps_are_named_after_their_devices() {
+        let root = TestDir::new("temps");
+        let sensors = get_sensors_from(&sensors_fixture(&root));
+        let gpu = |name: &str, slot: &str| Gpu {
+            vendor: String::new(),
+            name: name.to_string(),
+            vram: None,
+            driver: None,
+            kind: GpuKind::Discrete,
+            primary: false,
+            pci_slot: Some(slot.to_string()),
+            device_path: root.join("devices").join(slot),
+        };
+        fs::create_dir_all(root.join("devices/0000:01:00.0")).unwrap();
+        let gpus = [
+            gpu("Radeon RX 6800 XT", "0000:03:00.0"),
+            gpu("GeForce RTX 3060", "0000:01:00.0"),
+        ];
+
+        assert_eq!(
+            format_temps(&sensors, &gpus, &[Some(50.0), Some(61.0)], TempUnit::Celsius),
+            "CPU 48.5°C, Radeon RX 6800 XT 50.0°C, GeForce RTX 3060 61.0°C, nvme0 38.9°C, sda 35.0°C"
+        );
+        // Without a GPU list the hwmon chip is all there is.
+        assert_eq!(
+            format_temps(&sensors, &[], &[], TempUnit::Celsius),
+            "CPU 48.5°C, GPU 50.0°C, nvme0 38.9°C, sda 35.0°C"
+        );
+    }
+}
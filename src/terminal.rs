@@ -0,0 +1,122 @@
+use crate::process::{ancestors, our_ancestors, Process};
+use std::{env, process::Command};
+
+// Process names of terminal emulators, cut to 15 characters like /proc/<pid>/comm is.
+const TERMINALS: [(&str, &str); 37] = [
+    ("kitty", "kitty"),
+    ("alacritty", "Alacritty"),
+    ("wezterm-gui", "WezTerm"),
+    ("wezterm", "WezTerm"),
+    ("foot", "foot"),
+    ("footclient", "foot"),
+    ("ghostty", "Ghostty"),
+    ("gnome-terminal-", "GNOME Terminal"),
+    ("kgx", "Console"),
+    ("ptyxis", "Ptyxis"),
+    ("ptyxis-agent", "Ptyxis"),
+    ("konsole", "Konsole"),
+    ("yakuake", "Yakuake"),
+    ("xfce4-terminal", "Xfce Terminal"),
+    ("mate-terminal", "MATE Terminal"),
+    ("lxterminal", "LXTerminal"),
+    ("qterminal", "QTerminal"),
+    ("terminator", "Terminator"),
+    ("tilix", "Tilix"),
+    ("guake", "Guake"),
+    ("tilda", "Tilda"),
+    ("sakura", "Sakura"),
+    ("terminology", "Terminology"),
+    ("blackbox", "Black Box"),
+    ("contour", "Contour"),
+    ("rio", "Rio"),
+    ("cool-retro-term", "cool-retro-term"),
+    ("xterm", "XTerm"),
+    ("urxvt", "urxvt"),
+    ("urxvtd", "urxvt"),
+    ("rxvt", "rxvt"),
+    ("st", "st"),
+    ("hyper", "Hyper"),
+    ("tabby", "Tabby"),
+    ("warp", "Warp"),
+    ("code", "VS Code"),
+    ("login", "Linux console"),
+];
+
+const MULTIPLEXERS: [(&str, &str); 4] = [
+    ("tmux: server", "tmux"),
+    ("tmux", "tmux"),
+    ("screen", "screen"),
+    ("zellij", "zellij"),
+];
+
+fn find_terminal(processes: &[Process]) -> Option<&'static str> {
+    processes.iter().find_map(|process| {
+        TERMINALS
+            .iter()
+            .find(|(comm, _)| *comm == process.comm)
+            .map(|(_, name)| *name)
+    })
+}
+
+// The tmux server is started on its own, the terminal is above the attached client.
+fn tmux_client_terminal() -> Option<&'static str> {
+    let output = Command::new("tmux")
+        .args(["display-message", "-p", "#{client_pid}"])
+        .output()
+        .ok()?;
+    let pid = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    find_terminal(&ancestors(pid))
+}
+
+/// The terminal emulator this runs in, found by going up the process tree, with any
+/// multiplexer or SSH session in between.
+pub fn get_terminal() -> String {
+    let processes = our_ancestors();
+
+    let mut multiplexers: Vec<&str> = vec![];
+    for process in &processes {
+        if let Some((_, name)) = MULTIPLEXERS.iter().find(|(comm, _)| *comm == process.comm) {
+            if !multiplexers.contains(name) {
+                multiplexers.push(name);
+            }
+        }
+    }
+    if multiplexers.is_empty() && env::var("TMUX").is_ok() {
+        multiplexers.push("tmux");
+    }
+
+    // sshd-session is OpenSSH 9.8's per connection process.
+    let ssh = env::var("SSH_CONNECTION").is_ok()
+        || processes
+            .iter()
+            .any(|process| process.comm == "sshd" || process.comm == "sshd-session");
+
+    let terminal = find_terminal(&processes)
+        .or_else(|| {
+            multiplexers
+                .contains(&"tmux")
+                .then(tmux_client_terminal)
+                .flatten()
+        })
+        .map(String::from)
+        .or_else(|| {
+            env::var("TERM_PROGRAM")
+                .ok()
+                .filter(|program| !program.is_empty() && program != "tmux")
+        })
+        .or_else(|| env::var("TERM").ok())
+        .unwrap_or_default();
+
+    let details: Vec<&str> = multiplexers
+        .into_iter()
+        .chain(ssh.then_some("SSH"))
+        .collect();
+    if terminal.is_empty() || details.is_empty() {
+        terminal
+    } else {
+        format!("{} ({})", terminal, details.join(", "))
+    }
+}
@@ -0,0 +1,92 @@
+use crate::fns::home_dir;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::UNIX_EPOCH,
+};
+
+/// The first thing that looks like a version number, e.g. "5.2.15" from
+/// "GNU bash, version 5.2.15(1)-release (x86_64-pc-linux-gnu)" or "6.4" from "dwm-6.4".
+pub fn parse_version(output: &str) -> Option<String> {
+    output
+        .lines()
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ',' || c == '-'))
+        .map(|word| word.trim_start_matches('v'))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()) && word.contains('.'))
+        .map(|version| version.split('(').next().unwrap_or(version).to_string())
+}
+
+fn cache_path() -> PathBuf {
+    env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(format!("{}/.cache", home_dir())))
+        .join("rsftch/versions")
+}
+
+/// The version `exe` prints when run with `flag`, cached by path and modification time
+/// so it only runs again after it's been updated.
+pub fn cached_version(exe: &Path, flag: &str) -> Option<String> {
+    let modified = fs::metadata(exe)
+        .ok()?
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs()
+        .to_string();
+    let key = format!("{}\t{}\t", exe.display(), modified);
+
+    let cache_path = cache_path();
+    let cache = fs::read_to_string(&cache_path).unwrap_or_default();
+    if let Some(version) = cache.lines().find_map(|line| line.strip_prefix(&key)) {
+        return Some(version.to_string());
+    }
+
+    let output = Command::new(exe).arg(flag).output().ok()?;
+    // Some print their version to stderr.
+    let version = parse_version(&String::from_utf8_lossy(&output.stdout))
+        .or_else(|| parse_version(&String::from_utf8_lossy(&output.stderr)))?;
+
+    // Other versions of the same program are dropped, the cache is only an optimization.
+    let prefix = format!("{}\t", exe.display());
+    let mut lines: Vec<String> = cache
+        .lines()
+        .filter(|line| !line.starts_with(&prefix))
+        .map(String::from)
+        .collect();
+    lines.push(format!("{}{}", key, version));
+    if let Some(parent) = cache_path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(&cache_path, lines.join("\n") + "\n");
+
+    Some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_from_version_output() {
+        let cases = [
+            (
+                "GNU bash, version 5.2.15(1)-release (x86_64-pc-linux-gnu)",
+                Some("5.2.15"),
+            ),
+            ("zsh 5.9 (x86_64-pc-linux-gnu)", Some("5.9")),
+            ("fish, version 3.7.1", Some("3.7.1")),
+            ("sway version 1.9", Some("1.9")),
+            ("dwm-6.4", Some("6.4")),
+            ("Hyprland v0.41.2 built from branch", Some("0.41.2")),
+            ("KWin 6.0.5\n", Some("6.0.5")),
+            ("usage: dwm [-v]\ndwm-6.5, see LICENSE", Some("6.5")),
+            ("qtile", None),
+        ];
+
+        for (output, version) in cases {
+            assert_eq!(parse_version(output).as_deref(), version, "{:?}", output);
+        }
+    }
+}